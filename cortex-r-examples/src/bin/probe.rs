@@ -0,0 +1,33 @@
+//! Memory probe example for Arm Cortex-R
+
+#![no_std]
+#![no_main]
+
+// pull in our start-up code
+use cortex_r as _;
+use cortex_r_examples as _;
+
+use semihosting::println;
+
+static VALUE: u32 = 0x1234_5678;
+
+/// The entry-point to the Rust application.
+///
+/// It is called by the start-up code in `cortex-r-rt`.
+#[no_mangle]
+pub extern "C" fn kmain() {
+    let address = core::ptr::addr_of!(VALUE) as usize;
+
+    // Safety: reading our own static has no side-effects
+    let result = unsafe { cortex_r::probe::probe_read::<u32>(address) };
+    println!("Probing {:#010x} gave {:x?}", address, result);
+
+    // Turn on alignment checking, so that an unaligned read faults
+    cortex_r::register::Sctlr::modify(|w| w.set_a(true));
+
+    // Safety: reading our own static has no side-effects
+    let result = unsafe { cortex_r::probe::probe_read::<u32>(address + 1) };
+    println!("Probing {:#010x} gave {:x?}", address + 1, result);
+
+    semihosting::process::exit(0);
+}
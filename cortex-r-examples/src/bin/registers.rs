@@ -20,6 +20,9 @@ extern "C" {
 pub extern "C" fn kmain() {
     println!("{:?}", cortex_r::register::Midr::read());
     println!("{:?}", cortex_r::register::Cpsr::read());
+    println!("{:?}", cortex_r::register::Mpuir::read());
+    println!("{:?}", cortex_r::register::Ctr::read());
+    println!("{:?}", cortex_r::register::Clidr::read());
     #[cfg(arm_architecture = "v8-r")]
     {
         println!("{:?}", cortex_r::register::Cbar::read());
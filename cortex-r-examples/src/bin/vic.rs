@@ -0,0 +1,87 @@
+//! VIC example for Arm Cortex-R on a Versatile Application Baseboard
+
+#![no_std]
+#![no_main]
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+// pull in our start-up code
+use cortex_r as _;
+use cortex_r_examples as _;
+
+use cortex_r::vic::{Model, Source, Vic};
+use semihosting::println;
+
+/// The address of the PL190 on the Versatile Application Baseboard
+const VIC_BASE: *mut u32 = 0x1014_0000 as *mut u32;
+
+/// The source we raise a software interrupt on
+const SOFT_SOURCE: Source = Source::new(3);
+
+/// How many times our handler has run
+static HANDLED: AtomicU32 = AtomicU32::new(0);
+
+/// The entry-point to the Rust application.
+///
+/// It is called by the start-up code in `cortex-r-rt`.
+#[no_mangle]
+pub extern "C" fn kmain() {
+    if let Err(e) = main() {
+        panic!("main returned {:?}", e);
+    }
+    semihosting::process::exit(0);
+}
+
+/// The main function of our Rust application.
+///
+/// Called by [`kmain`].
+fn main() -> Result<(), core::fmt::Error> {
+    println!("Creating VIC driver");
+    let mut vic = unsafe { Vic::new(VIC_BASE, Model::Pl190) };
+    vic.init();
+    vic.set_vector(SOFT_SOURCE, soft_handler, 0);
+    vic.enable_interrupt(SOFT_SOURCE, true);
+
+    println!("Enabling interrupts...");
+    unsafe {
+        cortex_r::interrupt::enable();
+    }
+
+    println!("Raising software interrupt");
+    vic.set_pending(SOFT_SOURCE, true);
+
+    for _ in 0..1_000_000 {
+        if HANDLED.load(Ordering::Relaxed) != 0 {
+            break;
+        }
+        cortex_r::asm::nop();
+    }
+    println!("Handled {} interrupt(s)", HANDLED.load(Ordering::Relaxed));
+
+    Ok(())
+}
+
+/// The handler we put in the VIC for our software interrupt
+extern "C" fn soft_handler() {
+    println!("- IRQ handle {:?}", SOFT_SOURCE);
+    // Safety: main has finished setting up the VIC, and is only polling
+    let mut vic = unsafe { Vic::new(VIC_BASE, Model::Pl190) };
+    vic.set_pending(SOFT_SOURCE, false);
+    HANDLED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// QEMU doesn't model the VIC port, so we fetch the handler address ourselves
+#[no_mangle]
+unsafe extern "C" fn _irq_handler() {
+    println!("> IRQ");
+    // Safety: main has finished setting up the VIC, and is only polling
+    let vic = unsafe { Vic::new(VIC_BASE, Model::Pl190) };
+    let handler = vic.acknowledge_vector();
+    if handler != 0 {
+        // Safety: we only put `extern "C" fn()` handlers into the VIC
+        let handler: extern "C" fn() = unsafe { core::mem::transmute(handler) };
+        handler();
+    }
+    vic.end_interrupt(SOFT_SOURCE);
+    println!("< IRQ");
+}
@@ -0,0 +1,192 @@
+//! Attribute macros for the Arm Cortex-R run-time
+//!
+//! These are re-exported by `cortex-r-rt`, so use them from there.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, spanned::Spanned, Ident, ItemFn, LitInt, ReturnType, Type};
+
+/// Make a function the handler for an interrupt
+///
+/// Write `#[interrupt(id = 42)]` on a `fn()` (or a `fn() -> !`), and the IRQ
+/// dispatcher in `cortex-r-rt` calls it whenever the interrupt controller
+/// reports interrupt 42. The handler is called in IRQ mode, with IRQs masked -
+/// or with the `nested-interrupts` feature of `cortex-r-rt`, in System mode
+/// with IRQs unmasked.
+///
+/// The ID is the raw number the interrupt controller uses - e.g. 32 for the
+/// first GIC SPI. Giving two handlers the same ID is a link error.
+#[proc_macro_attribute]
+pub fn interrupt(args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut id: Option<LitInt> = None;
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("id") {
+            id = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("expected `id = <number>`"))
+        }
+    });
+    parse_macro_input!(args with parser);
+    let f = parse_macro_input!(input as ItemFn);
+
+    let Some(id) = id else {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "missing interrupt ID: use `#[interrupt(id = <number>)]`",
+        )
+        .to_compile_error()
+        .into();
+    };
+    let id_value = match id.base10_parse::<u32>() {
+        Ok(value) => value,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    let sig = &f.sig;
+    let returns_unit_or_never = match &sig.output {
+        ReturnType::Default => true,
+        ReturnType::Type(_, ty) => match ty.as_ref() {
+            Type::Never(_) => true,
+            Type::Tuple(tuple) => tuple.elems.is_empty(),
+            _ => false,
+        },
+    };
+    let valid_signature = sig.constness.is_none()
+        && sig.asyncness.is_none()
+        && sig.unsafety.is_none()
+        && sig.abi.is_none()
+        && sig.generics.params.is_empty()
+        && sig.generics.where_clause.is_none()
+        && sig.inputs.is_empty()
+        && sig.variadic.is_none()
+        && returns_unit_or_never;
+    if !valid_signature {
+        return syn::Error::new(
+            sig.span(),
+            "`#[interrupt]` handlers must have signature `fn()` or `fn() -> !`",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let ident = &sig.ident;
+    // Exporting a symbol named after the ID makes duplicates a link error
+    let symbol = format!("__cortex_r_rt_interrupt_{}", id_value);
+    quote!(
+        #f
+
+        const _: () = {
+            #[used]
+            #[link_section = ".irq_handlers"]
+            #[export_name = #symbol]
+            static HANDLER: ::cortex_r_rt::InterruptHandler = ::cortex_r_rt::InterruptHandler {
+                id: #id_value,
+                handler: || {
+                    #ident();
+                },
+            };
+        };
+    )
+    .into()
+}
+
+/// Make a function the handler for an Undefined Instruction, Prefetch Abort or
+/// Data Abort exception
+///
+/// Write `#[exception(Undefined)]` on a `fn(&ExceptionFrame) -> usize`, or
+/// `#[exception(PrefetchAbort)]` or `#[exception(DataAbort)]` on a
+/// `fn(&ExceptionFrame, &Fault) -> usize`. Either may return `!` instead. The
+/// trampoline in `cortex-r-rt` calls it in Undefined or Abort mode with the
+/// registers of the code which was running and (for aborts) the decoded fault,
+/// and resumes at the address it returns.
+///
+/// This exports an `extern "C"` function called `_undefined_handler`,
+/// `_prefetch_handler` or `_abort_handler`, replacing the default one. Giving
+/// two handlers the same exception is a link error.
+///
+/// A Data Abort caused by `cortex_r::probe::probe_read` never reaches a
+/// `DataAbort` handler - the wrapper returns to the probe, as the default
+/// handler does.
+#[proc_macro_attribute]
+pub fn exception(args: TokenStream, input: TokenStream) -> TokenStream {
+    let kind = parse_macro_input!(args as Ident);
+    let f = parse_macro_input!(input as ItemFn);
+
+    let (symbol, read_fault, fixup) = match kind.to_string().as_str() {
+        "Undefined" => ("_undefined_handler", None, false),
+        "PrefetchAbort" => (
+            "_prefetch_handler",
+            Some(quote!(read_prefetch_abort)),
+            false,
+        ),
+        "DataAbort" => ("_abort_handler", Some(quote!(read_data_abort)), true),
+        _ => {
+            return syn::Error::new(
+                kind.span(),
+                "expected `Undefined`, `PrefetchAbort` or `DataAbort`",
+            )
+            .to_compile_error()
+            .into();
+        }
+    };
+
+    let sig = &f.sig;
+    let num_inputs = if read_fault.is_some() { 2 } else { 1 };
+    let valid_signature = sig.constness.is_none()
+        && sig.asyncness.is_none()
+        && sig.unsafety.is_none()
+        && sig.abi.is_none()
+        && sig.generics.params.is_empty()
+        && sig.generics.where_clause.is_none()
+        && sig.inputs.len() == num_inputs
+        && sig.variadic.is_none()
+        && !matches!(sig.output, ReturnType::Default);
+    if !valid_signature {
+        let expected = if read_fault.is_some() {
+            "fn(&ExceptionFrame, &Fault) -> usize"
+        } else {
+            "fn(&ExceptionFrame) -> usize"
+        };
+        return syn::Error::new(
+            sig.span(),
+            format!(
+                "`#[exception({})]` handlers must have signature `{}` (or return `!`)",
+                kind, expected
+            ),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let ident = &sig.ident;
+    // Calling the handler from a wrapper with the C signature the trampoline
+    // expects means the compiler checks the argument and return types
+    let call = match read_fault {
+        Some(read_fault) => quote!(
+            let fault = ::cortex_r_rt::__Fault::#read_fault();
+            #ident(frame, &fault)
+        ),
+        None => quote!(#ident(frame)),
+    };
+    // Let the memory probe recover from its own aborts first
+    let fixup = fixup.then(|| {
+        quote!(
+            if let Some(resume) = ::cortex_r_rt::__fixup_abort(frame.pc as usize) {
+                return resume;
+            }
+        )
+    });
+    quote!(
+        #f
+
+        const _: () = {
+            #[export_name = #symbol]
+            extern "C" fn handler(frame: &::cortex_r_rt::ExceptionFrame) -> usize {
+                #fixup
+                #call
+            }
+        };
+    )
+    .into()
+}
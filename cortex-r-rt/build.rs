@@ -8,7 +8,23 @@ use std::io::Write;
 
 fn main() {
     arm_targets::process();
-    write("link.x", include_bytes!("link.x"));
+    let target = std::env::var("TARGET").expect("build script TARGET variable");
+    let mut link_x = include_bytes!("link.x").to_vec();
+    if std::env::var_os("CARGO_FEATURE_MEMORY_PROTECTION").is_some() {
+        // The MPU on each architecture has different alignment rules
+        if let Some(arm_targets::Arch::Armv8R) = arm_targets::Arch::get(&target) {
+            link_x.extend_from_slice(include_bytes!("memory-protection-v8.x"));
+        } else {
+            link_x.extend_from_slice(include_bytes!("memory-protection-v7.x"));
+        }
+    }
+    if std::env::var_os("CARGO_FEATURE_STACK_GUARD").is_some() {
+        link_x.extend_from_slice(include_bytes!("stack-guard.x"));
+    }
+    println!("cargo:rerun-if-changed=memory-protection-v7.x");
+    println!("cargo:rerun-if-changed=memory-protection-v8.x");
+    println!("cargo:rerun-if-changed=stack-guard.x");
+    write("link.x", &link_x);
 }
 
 fn write(file: &str, contents: &[u8]) {
@@ -15,16 +15,25 @@ EXTERN(_vector_table);
 
 SECTIONS {
     .text : {
+        __stext = .;
         /* The vector table must come first */
         *(.vector_table)
         /* Our exception handling routines */
         *(.text.handlers)
         /* Now the rest of the code */
         *(.text .text*)
+        __etext = .;
     } > CODE
 
     .rodata : {
+        __srodata = .;
         *(.rodata .rodata*)
+        /* The table of `#[interrupt]` handlers */
+        . = ALIGN(4);
+        __sirq_handlers = .;
+        KEEP(*(.irq_handlers));
+        __eirq_handlers = .;
+        __erodata = .;
     } > CODE
 
     .data : ALIGN(4) {
@@ -72,8 +81,9 @@ SECTIONS {
 }
 
 /*
-We reserve some space at the top of the RAM for our stacks. We have an IRQ stack
-and a FIQ stack, plus the remainder is our system stack.
+We reserve some space at the top of the RAM for our stacks. We have an FIQ
+stack, an IRQ stack, an SVC stack, an Abort stack and an Undefined stack, plus
+the remainder is our system stack.
 
 You must keep _stack_top and the stack sizes aligned to eight byte boundaries.
 */
@@ -81,15 +91,25 @@ PROVIDE(_stack_top = ORIGIN(DATA) + LENGTH(DATA));
 PROVIDE(_fiq_stack_size = 0x100);
 PROVIDE(_irq_stack_size = 0x1000);
 PROVIDE(_svc_stack_size = 0x1000);
+PROVIDE(_abt_stack_size = 0x400);
+PROVIDE(_und_stack_size = 0x400);
 
 ASSERT(_stack_top % 8 == 0, "ERROR(cortex-r-rt): top of stack is not 8-byte aligned");
 ASSERT(_fiq_stack_size % 8 == 0, "ERROR(cortex-r-rt): size of FIQ stack is not 8-byte aligned");
 ASSERT(_irq_stack_size % 8 == 0, "ERROR(cortex-r-rt): size of IRQ stack is not 8-byte aligned");
+ASSERT(_svc_stack_size % 8 == 0, "ERROR(cortex-r-rt): size of SVC stack is not 8-byte aligned");
+ASSERT(_abt_stack_size % 8 == 0, "ERROR(cortex-r-rt): size of Abort stack is not 8-byte aligned");
+ASSERT(_und_stack_size % 8 == 0, "ERROR(cortex-r-rt): size of Undefined stack is not 8-byte aligned");
 
-PROVIDE(_asm_undefined_handler =_asm_default_handler);
-PROVIDE(_asm_prefetch_handler  =_asm_default_handler);
-PROVIDE(_asm_abort_handler     =_asm_default_handler);
+PROVIDE(_asm_undefined_handler =_asm_default_undefined_handler);
+PROVIDE(_asm_prefetch_handler  =_asm_default_prefetch_handler);
+PROVIDE(_asm_abort_handler     =_asm_default_abort_handler);
 PROVIDE(_asm_fiq_handler       =_asm_default_fiq_handler);
-PROVIDE(_irq_handler           =_default_handler);
+PROVIDE(_irq_handler           =_default_irq_handler);
+PROVIDE(_fiq_handler           =_default_handler);
+PROVIDE(DefaultHandler         =_default_interrupt_handler);
 PROVIDE(_svc_handler           =_default_handler);
+PROVIDE(_undefined_handler     =_default_undefined_handler);
+PROVIDE(_prefetch_handler      =_default_prefetch_handler);
+PROVIDE(_abort_handler         =_default_abort_handler);
 PROVIDE(_start                 =_default_start);
@@ -0,0 +1,33 @@
+/*
+Extra linker script for the `memory-protection` feature on Armv7-R.
+
+PMSAv7 regions must be a power of two in size and aligned to their size, so we
+pad the end of the code (.text plus .rodata) out to the next power of two. The
+start of the CODE region must be suitably aligned, and so must the start of
+the DATA region (to the size of DATA, rounded up to a power of two). The
+subregions (eighths) of the DATA region which lie past the end of DATA are
+disabled, so DATA must end on a subregion boundary.
+*/
+
+SECTIONS {
+    .mpu_code_pad (NOLOAD) : {
+        . = ALIGN(MAX(32, 1 << LOG2CEIL(. - __stext)));
+        __ecode = .;
+    } > CODE
+} INSERT AFTER .rodata;
+
+__sram = ORIGIN(DATA);
+__eram = ORIGIN(DATA) + LENGTH(DATA);
+
+/*
+A no-access region is placed over the bottom of the address space to catch
+null pointer dereferences, unless code or RAM starts within it. Set this to
+zero to disable the guard.
+*/
+PROVIDE(_null_guard_size = 0x1000);
+
+ASSERT(__stext % (__ecode - __stext) == 0, "ERROR(cortex-r-rt): start of code is not aligned to the size of .text plus .rodata, rounded up to a power of two");
+ASSERT(_null_guard_size == 0 || (_null_guard_size >= 32 && (_null_guard_size & (_null_guard_size - 1)) == 0), "ERROR(cortex-r-rt): size of null guard is not zero or a power of two of at least 32 bytes");
+ASSERT(__ecode <= ORIGIN(CODE) + LENGTH(CODE), "ERROR(cortex-r-rt): .text plus .rodata, rounded up to a power of two, runs past the end of the CODE region");
+ASSERT(__sram % (1 << LOG2CEIL(MAX(32, __eram - __sram))) == 0, "ERROR(cortex-r-rt): start of DATA region is not aligned to the size of DATA, rounded up to a power of two");
+ASSERT((__eram - __sram) == (1 << LOG2CEIL(MAX(32, __eram - __sram))) || ((1 << LOG2CEIL(__eram - __sram)) >= 256 && (__eram - __sram) % ((1 << LOG2CEIL(__eram - __sram)) / 8) == 0), "ERROR(cortex-r-rt): size of DATA region is not a multiple of one eighth of its size rounded up to a power of two");
@@ -0,0 +1,30 @@
+/*
+Extra linker script for the `memory-protection` feature on Armv8-R.
+
+PMSAv8 regions have 64 byte granularity, so we pad the end of the code (.text
+plus .rodata) out to the next 64 byte boundary. The CODE and DATA regions must
+start (and DATA must end) on a 64 byte boundary.
+*/
+
+SECTIONS {
+    .mpu_code_pad (NOLOAD) : {
+        . = ALIGN(64);
+        __ecode = .;
+    } > CODE
+} INSERT AFTER .rodata;
+
+__sram = ORIGIN(DATA);
+__eram = ORIGIN(DATA) + LENGTH(DATA);
+
+/*
+Two identical regions are placed over the bottom of the address space to catch
+null pointer dereferences, unless code or RAM starts within it. Any access
+which hits both faults. Set this to zero to disable the guard.
+*/
+PROVIDE(_null_guard_size = 0x1000);
+
+ASSERT(__ecode <= ORIGIN(CODE) + LENGTH(CODE), "ERROR(cortex-r-rt): .text plus .rodata, padded to 64 bytes, runs past the end of the CODE region");
+ASSERT(__stext % 64 == 0, "ERROR(cortex-r-rt): start of code is not 64-byte aligned");
+ASSERT(__sram % 64 == 0, "ERROR(cortex-r-rt): start of DATA region is not 64-byte aligned");
+ASSERT(__eram % 64 == 0, "ERROR(cortex-r-rt): end of DATA region is not 64-byte aligned");
+ASSERT(_null_guard_size % 64 == 0, "ERROR(cortex-r-rt): size of null guard is not a multiple of 64 bytes");
@@ -0,0 +1,40 @@
+//! Enables the caches and branch prediction on start-up
+//!
+//! This is used by the `icache`, `dcache` and `branch-prediction` features. It
+//! runs after the MPU has been programmed (if the `memory-protection` feature
+//! is enabled), and before `kmain` is called.
+//!
+//! The contents of the caches and branch predictor are unknown at reset on
+//! some processors (e.g. the Cortex-R5), so each one is invalidated before it
+//! is enabled.
+
+use cortex_r::register::Sctlr;
+
+/// Invalidate and enable whichever caches were selected
+#[no_mangle]
+extern "C" fn _cache_init() {
+    #[cfg(feature = "branch-prediction")]
+    cortex_r::cache::invalidate_branch_predictor();
+
+    #[cfg(feature = "icache")]
+    cortex_r::cache::invalidate_icache_all();
+
+    // Safety: The data cache has never been enabled, so it holds no data that
+    // we need to keep.
+    #[cfg(feature = "dcache")]
+    unsafe {
+        cortex_r::cache::invalidate_dcache_all();
+    }
+
+    Sctlr::modify(|r| {
+        // On Armv8-R, branch prediction is always enabled and the Z bit is RES1
+        #[cfg(all(feature = "branch-prediction", arm_architecture = "v7-r"))]
+        r.set_z(true);
+        #[cfg(feature = "icache")]
+        r.set_i(true);
+        #[cfg(feature = "dcache")]
+        r.set_c(true);
+    });
+    cortex_r::asm::dsb();
+    cortex_r::asm::isb();
+}
@@ -0,0 +1,341 @@
+//! Dispatching interrupts to the handlers marked with `#[interrupt]`
+//!
+//! Each `#[interrupt(id = N)]` puts an [`InterruptHandler`] in the
+//! `.irq_handlers` section, which the linker script gathers into a table
+//! between `__sirq_handlers` and `__eirq_handlers`. The dispatcher acknowledges
+//! each pending interrupt with the interrupt controller given to
+//! [`set_interrupt_controller`], looks up its handler in the table, calls it,
+//! and then ends the interrupt.
+//!
+//! Handlers which are only known at run-time can be added to a second table,
+//! in RAM, with [`register`], and removed again with [`unregister`]. The
+//! dispatcher looks there for any interrupt without an `#[interrupt]` handler.
+//!
+//! With a GIC, [`register_deferred`] gives a handler to the SGI which
+//! `pend_deferred` raises, for work to be done at the lowest priority.
+
+use core::sync::atomic::{AtomicPtr, AtomicU32, Ordering};
+
+use cortex_r::interrupt::InterruptController;
+
+/// An entry in the table of interrupt handlers
+///
+/// The `#[interrupt]` attribute makes these for you.
+#[doc(hidden)]
+#[repr(C)]
+pub struct InterruptHandler {
+    /// The raw ID of the interrupt
+    pub id: u32,
+    /// The function to call
+    pub handler: fn(),
+}
+
+extern "C" {
+    static __sirq_handlers: u32;
+    static __eirq_handlers: u32;
+
+    /// Called with the raw ID of an interrupt which has no handler
+    fn DefaultHandler(id: u32);
+}
+
+/// The number of handlers which can be registered at run-time
+pub const REGISTERED_HANDLERS: usize = 32;
+
+/// A handler registered at run-time, which is given its context pointer
+pub type Handler = fn(context: *mut ());
+
+/// The ways in which registering a handler can fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegisterError {
+    /// A handler is already registered for this interrupt
+    AlreadyRegistered,
+    /// All [`REGISTERED_HANDLERS`] slots are in use
+    TableFull,
+    /// The interrupt ID is too large to be registered
+    InvalidId,
+}
+
+/// [`set_interrupt_controller`] has already been called
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ControllerAlreadySet;
+
+/// A slot in the table of handlers registered at run-time
+struct Slot {
+    /// The raw interrupt ID, or [`Slot::EMPTY`], or [`Slot::CLAIMED`]
+    id: AtomicU32,
+    /// The [`Handler`]
+    handler: AtomicPtr<()>,
+    /// The context pointer for the handler
+    context: AtomicPtr<()>,
+}
+
+impl Slot {
+    /// This slot is free
+    const EMPTY: u32 = u32::MAX;
+
+    /// This slot is being filled in by [`register`]
+    const CLAIMED: u32 = u32::MAX - 1;
+
+    const fn new() -> Slot {
+        Slot {
+            id: AtomicU32::new(Slot::EMPTY),
+            handler: AtomicPtr::new(core::ptr::null_mut()),
+            context: AtomicPtr::new(core::ptr::null_mut()),
+        }
+    }
+}
+
+/// The table of handlers registered at run-time
+static REGISTERED: [Slot; REGISTERED_HANDLERS] = [const { Slot::new() }; REGISTERED_HANDLERS];
+
+/// The interrupt controller the default IRQ handler uses (or null)
+///
+/// This is only ever set once, so it always matches `DISPATCHER`.
+static CONTROLLER: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// A `dispatch_erased::<C>` function matching `CONTROLLER` (or null, until
+/// `CONTROLLER` has been set)
+static DISPATCHER: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Get the table of interrupt handlers
+fn handlers() -> &'static [InterruptHandler] {
+    // Safety: the linker script puts the table between these two symbols, and
+    // it is only ever read
+    unsafe {
+        let start = core::ptr::addr_of!(__sirq_handlers).cast::<InterruptHandler>();
+        let end = core::ptr::addr_of!(__eirq_handlers).cast::<InterruptHandler>();
+        core::slice::from_raw_parts(start, end.offset_from(start) as usize)
+    }
+}
+
+/// Register a handler for an interrupt, at run-time
+///
+/// When the interrupt occurs, the dispatcher calls `handler(context)` (unless
+/// there's an `#[interrupt]` handler for it, which takes precedence). This
+/// doesn't enable the interrupt - do that once this returns.
+///
+/// This masks IRQs while it updates the table, so it is safe to call while
+/// IRQs are enabled, and even from an interrupt handler (but not from an FIQ
+/// handler).
+pub fn register<I>(id: I, handler: Handler, context: *mut ()) -> Result<(), RegisterError>
+where
+    I: Into<u32>,
+{
+    let id = id.into();
+    if id >= Slot::CLAIMED {
+        return Err(RegisterError::InvalidId);
+    }
+    // The dispatcher looks handlers up with IRQs masked, so it never sees a
+    // slot part-way through being changed
+    cortex_r::interrupt::free(|_cs| {
+        if find_registered(id).is_some() {
+            return Err(RegisterError::AlreadyRegistered);
+        }
+        let slot = REGISTERED
+            .iter()
+            .find(|slot| slot.id.load(Ordering::Relaxed) == Slot::EMPTY)
+            .ok_or(RegisterError::TableFull)?;
+        slot.id.store(Slot::CLAIMED, Ordering::Relaxed);
+        slot.handler.store(handler as *mut (), Ordering::Relaxed);
+        slot.context.store(context, Ordering::Relaxed);
+        // The dispatcher can see the slot from here on
+        slot.id.store(id, Ordering::Release);
+        Ok(())
+    })
+}
+
+/// Remove the handler registered at run-time for an interrupt
+///
+/// Returns the context pointer it was registered with, or `None` if there was
+/// no handler. Disable the interrupt, and make sure its handler isn't running,
+/// before calling this - otherwise the handler may still be using the context
+/// after this returns, and any further occurrence goes to `DefaultHandler`.
+pub fn unregister<I>(id: I) -> Option<*mut ()>
+where
+    I: Into<u32>,
+{
+    let id = id.into();
+    if id >= Slot::CLAIMED {
+        return None;
+    }
+    cortex_r::interrupt::free(|_cs| {
+        let slot = find_registered(id)?;
+        let context = slot.context.load(Ordering::Relaxed);
+        slot.id.store(Slot::EMPTY, Ordering::Release);
+        Some(context)
+    })
+}
+
+/// Register the handler for deferred work, at run-time
+///
+/// This is [`register`] for `cortex_r::gic::DEFERRED_SGI`. The handler runs
+/// when `cortex_r::gic::v2::Gic::pend_deferred` (or
+/// `cortex_r::gic::cpu::pend_deferred`) has been called, once every higher
+/// priority interrupt has been handled - so it suits context switching, and
+/// work which an interrupt handler wants to put off. With the
+/// `nested-interrupts` feature, every other interrupt can preempt it.
+pub fn register_deferred(handler: Handler, context: *mut ()) -> Result<(), RegisterError> {
+    register(cortex_r::gic::DEFERRED_SGI, handler, context)
+}
+
+/// Find the slot a handler for this interrupt was registered in
+fn find_registered(id: u32) -> Option<&'static Slot> {
+    REGISTERED
+        .iter()
+        .find(|slot| slot.id.load(Ordering::Acquire) == id)
+}
+
+/// Set the interrupt controller the default IRQ handler uses
+///
+/// Until this is called, an IRQ is treated as an unhandled exception. Set up
+/// the controller first, because it cannot be changed through this reference
+/// afterwards.
+///
+/// The controller can only be set once. Any later call (including one racing
+/// with the first) returns an error and changes nothing.
+pub fn set_interrupt_controller<C>(controller: &'static C) -> Result<(), ControllerAlreadySet>
+where
+    C: InterruptController + Sync,
+    C::Id: Into<u32>,
+{
+    CONTROLLER
+        .compare_exchange(
+            core::ptr::null_mut(),
+            controller as *const C as *mut (),
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+        )
+        .map_err(|_| ControllerAlreadySet)?;
+    // Only the winner of the exchange above gets here, so the dispatcher
+    // always matches the controller
+    DISPATCHER.store(dispatch_erased::<C> as *mut (), Ordering::Release);
+    Ok(())
+}
+
+/// Handle every pending interrupt on the given interrupt controller
+///
+/// Each one is passed to its `#[interrupt]` handler, or its handler from
+/// [`register`], or to `DefaultHandler` if it has neither. The default IRQ
+/// handler calls this for you, so you only need it if you supply your own
+/// `_irq_handler`. Call it with IRQs masked.
+///
+/// With the `nested-interrupts` feature, IRQs are unmasked while each handler
+/// runs, so that higher priority interrupts can preempt it. Only call it from
+/// `_irq_handler` in that case.
+pub fn dispatch<C>(controller: &C)
+where
+    C: InterruptController,
+    C::Id: Into<u32>,
+{
+    // The first interrupt's latency runs from entering the IRQ trampoline, and
+    // each later one's from when we went back to acknowledge it
+    #[cfg(feature = "stats")]
+    let mut entry = crate::stats::irq_entry();
+    while let Some(id) = controller.acknowledge() {
+        let raw_id = id.into();
+        #[cfg(feature = "stats")]
+        let start = cortex_r::pmu::cycle_count();
+        // Look the handler up while IRQs are still masked, so that `register`
+        // and `unregister` can't change its slot while we read it
+        let target = Target::find(raw_id);
+        // Safety: our IRQ trampoline has saved its state on the System mode
+        // stack, and the controller now only signals interrupts with a higher
+        // priority than this one
+        #[cfg(feature = "nested-interrupts")]
+        unsafe {
+            cortex_r::interrupt::enable();
+        }
+        match target {
+            Target::Static(handler) => handler(),
+            Target::Registered(handler, context) => handler(context),
+            // Safety: `DefaultHandler` is ours, or one the application supplied
+            // with the same signature
+            Target::Default => unsafe { DefaultHandler(raw_id) },
+        }
+        #[cfg(feature = "nested-interrupts")]
+        cortex_r::interrupt::disable();
+        #[cfg(feature = "stats")]
+        crate::stats::record_interrupt(raw_id, entry, start);
+        controller.end_interrupt(id);
+        #[cfg(feature = "stats")]
+        {
+            entry = cortex_r::pmu::cycle_count();
+        }
+    }
+}
+
+/// What the dispatcher calls for an interrupt
+enum Target {
+    /// An `#[interrupt]` handler
+    Static(fn()),
+    /// A handler from [`register`], with its context pointer
+    Registered(Handler, *mut ()),
+    /// Neither, so `DefaultHandler`
+    Default,
+}
+
+impl Target {
+    /// Find the handler for an interrupt
+    ///
+    /// Call with IRQs masked, so that the slot doesn't change under us.
+    fn find(raw_id: u32) -> Target {
+        if let Some(entry) = handlers().iter().find(|entry| entry.id == raw_id) {
+            Target::Static(entry.handler)
+        } else if let Some(slot) = find_registered(raw_id) {
+            let handler = slot.handler.load(Ordering::Relaxed);
+            let context = slot.context.load(Ordering::Relaxed);
+            // Safety: `register` only publishes a slot once it holds a `Handler`
+            let handler: Handler = unsafe { core::mem::transmute(handler) };
+            Target::Registered(handler, context)
+        } else {
+            Target::Default
+        }
+    }
+}
+
+/// Call [`dispatch`] with a controller we only have a type-erased pointer to
+///
+/// # Safety
+///
+/// `controller` must have come from a `&'static C`.
+unsafe fn dispatch_erased<C>(controller: *const ())
+where
+    C: InterruptController,
+    C::Id: Into<u32>,
+{
+    // Safety: passed on to our caller
+    dispatch(unsafe { &*(controller as *const C) });
+}
+
+/// Our default IRQ handler.
+///
+/// We end up here if an IRQ fires and the weak 'PROVIDE' in the link.x file
+/// hasn't been over-ridden. It dispatches to the `#[interrupt]` handlers, using
+/// the controller given to [`set_interrupt_controller`].
+#[cfg(target_arch = "arm")]
+#[no_mangle]
+pub extern "C" fn _default_irq_handler() {
+    let dispatcher = DISPATCHER.load(Ordering::Acquire);
+    if dispatcher.is_null() {
+        crate::_default_handler();
+        return;
+    }
+    let controller = CONTROLLER.load(Ordering::Relaxed);
+    // Safety: `set_interrupt_controller` stored a `&'static C` once, and then a
+    // matching `dispatch_erased::<C>`
+    unsafe {
+        let dispatcher: unsafe fn(*const ()) = core::mem::transmute(dispatcher);
+        dispatcher(controller);
+    }
+}
+
+/// Our default handler for interrupts with no `#[interrupt]` handler.
+///
+/// We end up here if the weak 'PROVIDE' of `DefaultHandler` in the link.x file
+/// hasn't been over-ridden.
+#[cfg(target_arch = "arm")]
+#[no_mangle]
+pub extern "C" fn _default_interrupt_handler(id: u32) {
+    semihosting::eprintln!("Unhandled interrupt {}!", id);
+    semihosting::process::abort();
+}
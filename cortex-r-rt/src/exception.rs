@@ -0,0 +1,64 @@
+//! The state saved by our Undefined, Prefetch Abort and Data Abort trampolines
+//!
+//! Each of these trampolines saves an [`ExceptionFrame`] on the stack of the
+//! mode it runs in (Undefined mode, or Abort mode for both kinds of abort) and
+//! then calls a handler with a pointer to it:
+//!
+//! * `extern "C" fn _undefined_handler(frame: &ExceptionFrame) -> usize`
+//! * `extern "C" fn _prefetch_handler(frame: &ExceptionFrame) -> usize`
+//! * `extern "C" fn _abort_handler(frame: &ExceptionFrame) -> usize`
+//!
+//! Each handler returns the address to resume execution at. The abort handlers
+//! can decode the fault with [`Fault::read_prefetch_abort`] or
+//! [`Fault::read_data_abort`]. The easiest way to supply your own is with the
+//! [`exception`](macro@crate::exception) attribute, which does that for you and
+//! checks your function's signature.
+//!
+//! [`Fault::read_prefetch_abort`]: cortex_r::abort::Fault::read_prefetch_abort
+//! [`Fault::read_data_abort`]: cortex_r::abort::Fault::read_data_abort
+
+use cortex_r::register::Cpsr;
+
+/// The registers of the code which was running when an exception was taken
+///
+/// The banked registers (r8-r12, SP and LR) are those of the mode that code was
+/// running in - so if it was in FIQ mode, r8-r12 are FIQ mode's own copies.
+/// That mode can be found in the SPSR.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct ExceptionFrame {
+    /// The general purpose registers, r0 to r12
+    pub r: [u32; 13],
+    /// The stack pointer (r13)
+    pub sp: u32,
+    /// The link register (r14)
+    pub lr: u32,
+    /// The address of the instruction which caused the exception
+    pub pc: u32,
+    /// The saved program status register, which holds the interrupted CPSR
+    pub spsr: u32,
+}
+
+impl ExceptionFrame {
+    /// The CPSR of the code which was running when the exception was taken
+    pub fn cpsr(&self) -> Cpsr {
+        Cpsr::new_with_raw_value(self.spsr)
+    }
+}
+
+impl core::fmt::Debug for ExceptionFrame {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "ExceptionFrame {{")?;
+        for (idx, value) in self.r.iter().enumerate() {
+            write!(f, " R{}=0x{:08x}", idx, value)?;
+        }
+        write!(
+            f,
+            " SP=0x{:08x} LR=0x{:08x} PC=0x{:08x} SPSR={:?} }}",
+            self.sp,
+            self.lr,
+            self.pc,
+            self.cpsr()
+        )
+    }
+}
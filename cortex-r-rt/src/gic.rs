@@ -0,0 +1,82 @@
+//! Finds and initialises the GICv3 on start-up
+//!
+//! This is used by the `gic` feature. `_gic_init` runs just before `kmain` is
+//! called. It finds the Distributor and this core's Redistributor using CBAR,
+//! initialises them along with the CPU interface, and gives the IRQ dispatcher
+//! a way to acknowledge and end interrupts. The application then takes the
+//! driver with [`take_gic`], to configure and enable its interrupts.
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use cortex_r::gic::{v3::Gic, IntId};
+use cortex_r::interrupt::InterruptController;
+
+/// Somewhere to keep the driver until the application takes it
+struct GicCell(UnsafeCell<Option<Gic>>);
+
+// Safety: `_gic_init` fills it in before `kmain` runs, and after that only
+// `take_gic` touches it, once
+unsafe impl Sync for GicCell {}
+
+static GIC: GicCell = GicCell(UnsafeCell::new(None));
+
+/// Has [`take_gic`] been called?
+static TAKEN: AtomicBool = AtomicBool::new(false);
+
+/// Acknowledges and ends interrupts through this core's GIC CPU interface
+///
+/// This is what the IRQ dispatcher is given, so that the application can own
+/// the [`Gic`]. The dispatcher only has a shared reference, so the methods
+/// which take `&mut self` are never called.
+struct CpuInterface;
+
+impl InterruptController for CpuInterface {
+    type Id = IntId;
+
+    fn enable(&mut self, _id: IntId) {}
+
+    fn disable(&mut self, _id: IntId) {}
+
+    fn set_pending(&mut self, _id: IntId, _pending: bool) {}
+
+    fn set_priority(&mut self, _id: IntId, _priority: u8) {}
+
+    fn acknowledge(&self) -> Option<IntId> {
+        cortex_r::gic::cpu::acknowledge()
+    }
+
+    fn end_interrupt(&self, id: IntId) {
+        cortex_r::gic::cpu::end_interrupt(id);
+    }
+}
+
+static CPU_INTERFACE: CpuInterface = CpuInterface;
+
+/// Take the GIC driver that the start-up code initialised
+///
+/// Returns `None` if it has already been taken, or if the start-up code found
+/// no Redistributor for this core (in which case IRQs are still treated as
+/// unhandled exceptions).
+pub fn take_gic() -> Option<Gic> {
+    if TAKEN.swap(true, Ordering::Acquire) {
+        return None;
+    }
+    // Safety: we only get here once, and `_gic_init` finished before `kmain`
+    unsafe { (*GIC.0.get()).take() }
+}
+
+/// Find and initialise the GIC, and hand it to the IRQ dispatcher
+#[no_mangle]
+extern "C" fn _gic_init() {
+    // Safety: the `gic` feature is only for processors with the standard GIC
+    // layout, and this is the only driver until the application takes it
+    let Some(mut gic) = (unsafe { Gic::from_cbar() }) else {
+        return;
+    };
+    gic.init();
+    // Safety: nothing else is running yet
+    unsafe { *GIC.0.get() = Some(gic) };
+    // Nothing else can have set a controller before `kmain`
+    let _ = crate::set_interrupt_controller(&CPU_INTERFACE);
+}
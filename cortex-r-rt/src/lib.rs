@@ -23,24 +23,48 @@
 //!   when in FIQ mode; must be a multiple of 8.
 //! * `_svc_stack_size` - the number of bytes to be reserved for stack space
 //!   when in SVC mode; must be a multiple of 8.F
+//! * `_abt_stack_size` - the number of bytes to be reserved for stack space
+//!   when in Abort mode; must be a multiple of 8.
+//! * `_und_stack_size` - the number of bytes to be reserved for stack space
+//!   when in Undefined mode; must be a multiple of 8.
 //! * `_svc_handler` - an `extern "C"` function to call when an SVC Exception
 //!   occurs. Our linker script PROVIDEs a default function at
 //!   `_default_handler` but you can override it.
 //! * `_irq_handler` - an `extern "C"` function to call when an Interrupt
 //!   occurs. Our linker script PROVIDEs a default function at
-//!   `_default_handler` but you can override it.
+//!   `_default_irq_handler` but you can override it.
+//! * `DefaultHandler` - an `extern "C" fn(id: u32)` to call when an Interrupt
+//!   with no `#[interrupt]` handler occurs. Our linker script PROVIDEs a
+//!   default function at `_default_interrupt_handler` but you can override it.
 //! * `_asm_fiq_handler` - a naked function to call when a Fast Interrupt
 //!   Request (FIQ) occurs. Our linker script PROVIDEs a default function at
-//!   `_asm_default_fiq_handler` but you can override it.
+//!   `_asm_default_fiq_handler` but you can override it. With the
+//!   `fiq-trampoline` feature, we supply it instead.
+//! * `_fiq_handler` - an `extern "C"` function for our `_asm_fiq_handler` to
+//!   call, when the `fiq-trampoline` feature is enabled. Our linker script
+//!   PROVIDEs a default function at `_default_handler` but you can override
+//!   it.
 //! * `_asm_undefined_handler` - a naked function to call when an Undefined
 //!   Exception occurs. Our linker script PROVIDEs a default function at
-//!   `_asm_default_handler` but you can override it.
+//!   `_asm_default_undefined_handler` but you can override it.
 //! * `_asm_prefetch_handler` - a naked function to call when an Prefetch
 //!   Exception occurs. Our linker script PROVIDEs a default function at
-//!   `_asm_default_handler` but you can override it.
+//!   `_asm_default_prefetch_handler` but you can override it.
 //! * `_asm_abort_handler` - a naked function to call when an Abort Exception
 //!   occurs. Our linker script PROVIDEs a default function at
-//!   `_asm_default_handler` but you can override it.
+//!   `_asm_default_abort_handler` but you can override it.
+//! * `_undefined_handler` - an `extern "C"` function like
+//!   `fn(frame: &ExceptionFrame) -> usize` to call when an Undefined
+//!   Instruction exception occurs. Our linker script PROVIDEs a default
+//!   function at `_default_undefined_handler` but you can override it.
+//! * `_prefetch_handler` - an `extern "C"` function like
+//!   `fn(frame: &ExceptionFrame) -> usize` to call when a Prefetch Abort
+//!   occurs. Our linker script PROVIDEs a default function at
+//!   `_default_prefetch_handler` but you can override it.
+//! * `_abort_handler` - an `extern "C"` function like
+//!   `fn(frame: &ExceptionFrame) -> usize` to call when a Data Abort occurs.
+//!   Our linker script PROVIDEs a default function at `_default_abort_handler`
+//!   but you can override it.
 //! * `kmain` - the `extern "C"` entry point to your application.
 //! * `__sdata` - the start of initialised data in RAM. Must be 4-byte aligned.
 //! * `__edata` - the end of initialised data in RAM. Must be 4-byte aligned.
@@ -61,11 +85,32 @@
 //! * `_default_start` - the default Reset handler, that sets up some stacks and
 //!   calls an `extern "C"` function called `kmain`.
 //! * `_asm_default_fiq_handler` - an FIQ handler that just spins
+//! * `_asm_fiq_handler` - assembly language trampoline for Fast Interrupts that
+//!   calls `_fiq_handler`, with the `fiq-trampoline` feature
 //! * `_asm_default_handler` - an exception handler that just spins
 //! * `_asm_svc_handler` - assembly language trampoline for SVC Exceptions that
 //!   calls `_svc_handler`
 //! * `_asm_irq_handler` - assembly language trampoline for Interrupts that
-//!   calls `_irq_handler`
+//!   calls `_irq_handler` (in System mode, with the `nested-interrupts`
+//!   feature)
+//! * `_asm_default_undefined_handler` - assembly language trampoline for
+//!   Undefined Instruction exceptions that calls `_undefined_handler`
+//! * `_asm_default_prefetch_handler` - assembly language trampoline for
+//!   Prefetch Aborts that calls `_prefetch_handler`
+//! * `_asm_default_abort_handler` - assembly language trampoline for Data
+//!   Aborts that calls `_abort_handler`
+//! * `_default_undefined_handler` - an Undefined Instruction handler that
+//!   reports the registers and stops
+//! * `_default_prefetch_handler` - a Prefetch Abort handler that reports the
+//!   fault and the registers, and stops
+//! * `_default_abort_handler` - a Data Abort handler that recovers from aborts
+//!   caused by `cortex_r::probe::probe_read`, and otherwise reports the fault
+//!   and the registers, and stops
+//! * `_default_irq_handler` - an IRQ handler that dispatches to the
+//!   `#[interrupt]` handlers, or reports an unhandled exception if
+//!   [`set_interrupt_controller`] hasn't been called
+//! * `_default_interrupt_handler` - an interrupt handler that reports the
+//!   interrupt ID and stops
 //!
 //! The assembly language trampolines are required because Armv7-R (and Armv8-R)
 //! processors do not save a great deal of state on entry to an exception
@@ -75,9 +120,135 @@
 //! `extern "C"` function - that's for the handler to deal with as it wishes. We
 //! supply a default handler that prints an error message to Semihosting so you
 //! know if you hit an unexpected exception. Because FIQ is often
-//! performance-sensitive, we don't supply an FIQ trampoline; if you want to use
-//! FIQ, you have to write your own assembly routine, allowing you to preserve
-//! only whatever state is important to you.
+//! performance-sensitive, we don't supply an FIQ trampoline by default; either
+//! write your own assembly routine, allowing you to preserve only whatever
+//! state is important to you, or enable the `fiq-trampoline` feature. That
+//! trampoline relies on FIQ mode's banked r8-r12, saving only r0-r3, LR and
+//! (if there is an FPU) d0-d7 before calling `_fiq_handler`. It uses the
+//! banked r8 and r9 to hold FPSCR and FPEXC, so your handler can't keep its
+//! own values in them between FIQs. To route an interrupt to FIQ, put it in
+//! Group 0 of the GIC (e.g. with `cortex_r::gic::v2::Gic::set_group`) and
+//! acknowledge it from `_fiq_handler`.
+//!
+//! The Undefined Instruction, Prefetch Abort and Data Abort trampolines save
+//! every register of the code which was running - r0-r12, its SP and LR, the
+//! address of the instruction which caused the exception and the SPSR - as an
+//! [`ExceptionFrame`], on the Undefined mode or Abort mode stack. They then
+//! call an `extern "C"` handler with a pointer to the frame. Each handler
+//! returns the address to resume at, with r0-r12 restored from the frame. To
+//! supply your own, mark a `fn(&ExceptionFrame) -> usize` with
+//! `#[exception(Undefined)]`, or a
+//! `fn(&ExceptionFrame, &cortex_r::abort::Fault) -> usize` with
+//! `#[exception(PrefetchAbort)]` or `#[exception(DataAbort)]` (see
+//! [`exception`](macro@exception)). The attribute decodes the fault for you,
+//! and checks the signature. For Data Aborts, it first lets
+//! `cortex_r::probe::fixup_abort` recover from aborts caused by
+//! `cortex_r::probe::probe_read`, so your handler only sees the others. If you
+//! write `_abort_handler` yourself instead, call `fixup_abort` first.
+//!
+//! Mark each of your interrupt handlers with [`interrupt`], and give your
+//! interrupt controller (anything which implements
+//! `cortex_r::interrupt::InterruptController`, like `cortex_r::gic::v3::Gic`)
+//! to [`set_interrupt_controller`]. The default IRQ handler then acknowledges
+//! each interrupt, calls its handler, and ends it. The handlers are gathered
+//! into a table in `.rodata`, between `__sirq_handlers` and
+//! `__eirq_handlers`. Handlers for interrupts which are only known at run-time
+//! can be added with [`register`] and removed with [`unregister`]. For work
+//! to be done at the lowest priority (like PendSV on Cortex-M), give a
+//! handler to [`register_deferred`], and raise it with the GIC's
+//! `pend_deferred`.
+//!
+//! If the `memory-protection` feature is enabled, the start-up routine also
+//! programs the MPU, just before calling `kmain`. The code (`.text` and
+//! `.rodata`) is made read-only and executable, the DATA region is made
+//! read-write and never executable, and a guard region is placed at address
+//! zero to catch null pointer dereferences. Guard regions are no-access on
+//! Armv7-R. PMSAv8 cannot stop privileged code reading memory, so on Armv8-R
+//! the stack guards are read-only and never executable, and only writes and
+//! instruction fetches fault. The null guard there is two identical regions,
+//! because an access which hits two regions always faults, but the second is
+//! left out if the MPU has no room for it (which can happen when the code is
+//! in DATA and every stack is guarded). The linker script then also uses:
+//!
+//! * `__stext` - the start of `.text`. Must be aligned to suit the MPU.
+//! * `__ecode` - the end of `.rodata`, padded to suit the MPU.
+//! * `__sram` and `__eram` - the start and end of the DATA region. On Armv7-R,
+//!   the start must be aligned to the size of DATA, rounded up to a power of
+//!   two, and the size must be a multiple of one eighth of that (so that the
+//!   MPU region can be trimmed to fit with subregions).
+//! * `_null_guard_size` - the size of the guard region at address zero.
+//!   Our linker script PROVIDEs a default of 4 KiB; set it to zero to disable
+//!   the guard.
+//!
+//! Any other address (e.g. peripherals) falls back to the default memory map,
+//! which is only accessible to privileged code.
+//!
+//! If the `stack-guard` feature is enabled (which implies `memory-protection`),
+//! a gap is left below each of the stacks and the MPU makes each gap a guard
+//! region, so that a stack overflow causes a Data Abort. Use
+//! [`overflowed_stack`] with the fault address to find out which stack it was.
+//! On Armv7-R, the guards are programmed in the order System, IRQ, SVC, Abort,
+//! Undefined and then FIQ, and any that the MPU has no room for are left out.
+//! A Cortex-R4 with an 8 region MPU therefore cannot guard the FIQ stack,
+//! unless the null guard is disabled. On Armv8-R, the DATA region is split
+//! around the guards so that no regions overlap, which needs at most 16
+//! regions (17 if the code is in DATA). The linker script then also uses:
+//!
+//! * `_stack_guard_size` - the size of each gap. Must be a power of two, of at
+//!   least 64 bytes. `_stack_top` and every stack size must be a multiple of
+//!   it. Our linker script PROVIDEs a default of 64 bytes.
+//! * `_sys_stack_size` - the number of bytes to be reserved for stack space
+//!   when in System mode. Our linker script PROVIDEs a default of 16 KiB.
+//!
+//! The caches and branch prediction are left disabled, unless you enable
+//! these features:
+//!
+//! * `icache` - invalidate and enable the instruction cache
+//! * `dcache` - invalidate and enable the data cache. This needs the MPU to
+//!   mark RAM as cacheable, so it implies `memory-protection`.
+//! * `branch-prediction` - invalidate and enable branch prediction. On Armv8-R,
+//!   branch prediction is always enabled, so this only invalidates it.
+//!
+//! This happens after the MPU is programmed, just before calling `kmain`.
+//!
+//! If the `vectored-interrupts` feature is enabled (Armv7-R only), the start-up
+//! routine also sets SCTLR.VE, just before calling `kmain`. IRQs then skip
+//! `_asm_irq_handler`, and the core branches straight to the handler address
+//! that a VIC (e.g. a PL192) supplies over its VIC port. Each handler needs a
+//! trampoline, made with [`vectored_interrupt!`], and that trampoline is what
+//! you give to `cortex_r::vic::Vic::set_vector`.
+//!
+//! If the `gic` feature is enabled (Armv8-R only), the start-up routine also
+//! finds the GIC using CBAR and initialises it with
+//! `cortex_r::gic::v3::Gic::init`, just before calling `kmain`. The default IRQ
+//! handler then acknowledges and ends interrupts through the GIC CPU interface,
+//! so don't call [`set_interrupt_controller`] yourself. Call [`take_gic`] to
+//! get the driver, and use it to set up and enable your interrupts.
+//!
+//! If the `nested-interrupts` feature is enabled, `_asm_irq_handler` switches
+//! to System mode, saves LR_irq, SPSR_irq and the caller-saved registers on the
+//! System mode stack, and calls `_irq_handler`. System mode shares its SP with
+//! User mode, so this is whichever stack SP_usr points at - the interrupted
+//! code's own stack if it was in User or System mode (e.g. `kmain`'s, or a User
+//! mode task's), and otherwise the stack of the User or System mode code which
+//! ran before it (e.g. the task which made an SVC call). The dispatcher then
+//! unmasks IRQs while each handler runs, once the interrupt has been
+//! acknowledged - from that point the interrupt controller only signals
+//! interrupts with a higher priority (e.g. with a GIC, a higher group priority
+//! than the running priority), so those can preempt the handler. Each level of
+//! nesting therefore takes up to 40 bytes of saved state (112 bytes with an
+//! FPU, and 8 more with the `stats` feature), plus whatever the dispatcher and
+//! that handler use. Every stack which System or User mode code runs on -
+//! `kmain`'s, and that of each User mode task - must have room for its own use
+//! plus one such level for every priority level that can preempt another. This
+//! doesn't affect the trampolines made with [`vectored_interrupt!`].
+//!
+//! If the `stats` feature is enabled, the start-up routine also starts the
+//! PMU cycle counter, just before calling `kmain`. Our trampolines then count
+//! each exception and time its handler, and the IRQ dispatcher does the same
+//! for each interrupt ID, along with its latency. Read the results with the
+//! functions in [`stats`]. The trampolines made with [`vectored_interrupt!`]
+//! are not counted.
 //!
 //! If our start-up routine doesn't work for you (e.g. if you have to initialise
 //! your memory controller before you touch RAM), supply your own `_start`
@@ -85,21 +256,152 @@
 
 #![no_std]
 
+#[cfg(any(arm_architecture = "v7-r", arm_architecture = "v8-r"))]
 use cortex_r::register::{cpsr::ProcessorMode, Cpsr};
 
 #[cfg(arm_architecture = "v8-r")]
 use cortex_r::register::Hactlr;
 
+#[cfg(any(arm_architecture = "v7-r", arm_architecture = "v8-r"))]
+use cortex_r::abort::Fault;
+
+#[cfg(all(
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
+    feature = "memory-protection"
+))]
+mod mpu;
+
+#[cfg(all(
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
+    any(feature = "icache", feature = "dcache", feature = "branch-prediction")
+))]
+mod cache;
+
+#[cfg(feature = "stack-guard")]
+mod stack_guard;
+
+#[cfg(feature = "stats")]
+pub mod stats;
+
+#[cfg(all(arm_architecture = "v8-r", feature = "gic"))]
+mod gic;
+
+#[cfg(any(arm_architecture = "v7-r", arm_architecture = "v8-r"))]
+mod exception;
+
+mod dispatch;
+
+#[doc(inline)]
+pub use cortex_r_rt_macros::interrupt;
+
+#[cfg(any(arm_architecture = "v7-r", arm_architecture = "v8-r"))]
+#[doc(inline)]
+pub use cortex_r_rt_macros::exception;
+
+#[doc(inline)]
+pub use dispatch::{
+    dispatch, register, register_deferred, set_interrupt_controller, unregister,
+    ControllerAlreadySet, Handler, InterruptHandler, RegisterError, REGISTERED_HANDLERS,
+};
+
+#[cfg(any(arm_architecture = "v7-r", arm_architecture = "v8-r"))]
+#[doc(inline)]
+pub use exception::ExceptionFrame;
+
+#[cfg(feature = "stack-guard")]
+#[doc(inline)]
+pub use stack_guard::{overflowed_stack, Stack};
+
+#[cfg(all(arm_architecture = "v8-r", feature = "gic"))]
+#[doc(inline)]
+pub use gic::take_gic;
+
+#[cfg(all(arm_architecture = "v8-r", feature = "vectored-interrupts"))]
+compile_error!("Armv8-R processors have no VIC port, so cannot use vectored-interrupts");
+
+#[cfg(all(arm_architecture = "v7-r", feature = "gic"))]
+compile_error!("The gic feature is for the GICv3 in Armv8-R processors");
+
+#[doc(hidden)]
+pub use cortex_r::register::cpsr::ProcessorMode as __ProcessorMode;
+
+#[cfg(any(arm_architecture = "v7-r", arm_architecture = "v8-r"))]
+#[doc(hidden)]
+pub use cortex_r::abort::Fault as __Fault;
+
+#[cfg(all(
+    target_arch = "arm",
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r")
+))]
+#[doc(hidden)]
+pub use cortex_r::probe::fixup_abort as __fixup_abort;
+
 /// Our default exception handler.
 ///
 /// We end up here if an exception fires and the weak 'PROVIDE' in the link.x
 /// file hasn't been over-ridden.
+#[cfg(target_arch = "arm")]
 #[no_mangle]
 pub extern "C" fn _default_handler() {
     semihosting::eprintln!("Unhandled exception!");
     semihosting::process::abort();
 }
 
+/// Our default Undefined Instruction handler.
+///
+/// We end up here if an Undefined Instruction exception occurs and the weak
+/// 'PROVIDE' in the link.x file hasn't been over-ridden. We report where it
+/// happened, and the registers at the time, and stop.
+#[cfg(any(arm_architecture = "v7-r", arm_architecture = "v8-r"))]
+#[no_mangle]
+pub extern "C" fn _default_undefined_handler(frame: &ExceptionFrame) -> usize {
+    semihosting::eprintln!("Undefined Instruction at 0x{:08x}", frame.pc);
+    semihosting::eprintln!("{:?}", frame);
+    semihosting::process::abort();
+}
+
+/// Our default Prefetch Abort handler.
+///
+/// We end up here if a Prefetch Abort occurs and the weak 'PROVIDE' in the
+/// link.x file hasn't been over-ridden. We report the fault, and the registers
+/// at the time, and stop.
+#[cfg(any(arm_architecture = "v7-r", arm_architecture = "v8-r"))]
+#[no_mangle]
+pub extern "C" fn _default_prefetch_handler(frame: &ExceptionFrame) -> usize {
+    let fault = Fault::read_prefetch_abort();
+    semihosting::eprintln!("Prefetch Abort at 0x{:08x}: {:?}", frame.pc, fault);
+    semihosting::eprintln!("{:?}", frame);
+    semihosting::process::abort();
+}
+
+/// Our default Data Abort handler.
+///
+/// We end up here if a Data Abort occurs and the weak 'PROVIDE' in the link.x
+/// file hasn't been over-ridden. If the abort was caused by
+/// `cortex_r::probe::probe_read`, we return to the probe. Otherwise we report
+/// the fault (distinguishing asynchronous aborts from Data Aborts), and the
+/// registers at the time, and stop.
+#[cfg(any(arm_architecture = "v7-r", arm_architecture = "v8-r"))]
+#[no_mangle]
+pub extern "C" fn _default_abort_handler(frame: &ExceptionFrame) -> usize {
+    if let Some(resume) = cortex_r::probe::fixup_abort(frame.pc as usize) {
+        return resume;
+    }
+    let fault = Fault::read_data_abort();
+    if fault.kind.is_async() {
+        // We only know roughly where we were when the abort was taken
+        semihosting::eprintln!("Asynchronous Abort near 0x{:08x}: {:?}", frame.pc, fault);
+    } else {
+        semihosting::eprintln!("Data Abort at 0x{:08x}: {:?}", frame.pc, fault);
+    }
+    semihosting::eprintln!("{:?}", frame);
+    #[cfg(feature = "stack-guard")]
+    if let Some(stack) = fault.address.and_then(overflowed_stack) {
+        semihosting::eprintln!("{:?} stack overflowed!", stack);
+    }
+    semihosting::process::abort();
+}
+
 // The Interrupt Vector Table, and some default assembly-language handler.
 #[cfg(any(arm_architecture = "v7-r", arm_architecture = "v8-r"))]
 core::arch::global_asm!(
@@ -146,6 +448,8 @@ core::arch::global_asm!(
     any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
     not(any(target_abi = "eabihf", feature = "eabi-fpu"))
 ))]
+#[doc(hidden)]
+#[macro_export]
 macro_rules! save_context {
     () => {
         r#"
@@ -169,6 +473,8 @@ macro_rules! save_context {
     any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
     not(any(target_abi = "eabihf", feature = "eabi-fpu"))
 ))]
+#[doc(hidden)]
+#[macro_export]
 macro_rules! restore_context {
     () => {
         r#"
@@ -190,6 +496,8 @@ macro_rules! restore_context {
     any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
     any(target_abi = "eabihf", feature = "eabi-fpu")
 ))]
+#[doc(hidden)]
+#[macro_export]
 macro_rules! save_context {
     () => {
         r#"
@@ -218,6 +526,8 @@ macro_rules! save_context {
     any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
     any(target_abi = "eabihf", feature = "eabi-fpu")
 ))]
+#[doc(hidden)]
+#[macro_export]
 macro_rules! restore_context {
     () => {
         r#"
@@ -236,7 +546,87 @@ macro_rules! restore_context {
     };
 }
 
-// Our assembly language exception handlers
+/// This macro expands to code which counts an exception, and notes when our
+/// trampoline started handling it.
+///
+/// It should match `stats_exit!`, and goes after `save_context!`. LR is
+/// preserved. The argument is the `stats::Exception` value.
+#[cfg(all(
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
+    feature = "stats"
+))]
+macro_rules! stats_enter {
+    ($exception:literal) => {
+        concat!(
+            r#"
+        // keep LR, and make room for the time
+        push    {{r0, lr}}
+        mov     r0, "#,
+            $exception,
+            r#"
+        bl      _stats_enter
+        str     r0, [sp]
+        ldr     lr, [sp, 4]
+        "#
+        )
+    };
+}
+
+/// This macro expands to code which records how long an exception's handler
+/// took.
+///
+/// It should match `stats_enter!`, and goes before `restore_context!`. LR is
+/// preserved.
+#[cfg(all(
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
+    feature = "stats"
+))]
+macro_rules! stats_exit {
+    ($exception:literal) => {
+        concat!(
+            r#"
+        // get the time back, and keep LR
+        ldr     r1, [sp]
+        str     lr, [sp, 4]
+        mov     r0, "#,
+            $exception,
+            r#"
+        bl      _stats_exit
+        pop     {{r1, lr}}
+        "#
+        )
+    };
+}
+
+/// This macro expands to code that does nothing because statistics are not
+/// enabled
+#[cfg(all(
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
+    not(feature = "stats")
+))]
+macro_rules! stats_enter {
+    ($exception:literal) => {
+        r#"
+        // no stats - do nothing
+        "#
+    };
+}
+
+/// This macro expands to code that does nothing because statistics are not
+/// enabled
+#[cfg(all(
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
+    not(feature = "stats")
+))]
+macro_rules! stats_exit {
+    ($exception:literal) => {
+        r#"
+        // no stats - do nothing
+        "#
+    };
+}
+
+// Our assembly language SVC handler
 #[cfg(any(arm_architecture = "v7-r", arm_architecture = "v8-r"))]
 core::arch::global_asm!(
     r#"
@@ -254,6 +644,7 @@ core::arch::global_asm!(
         srsfd   sp!, {svc_mode}
     "#,
     save_context!(),
+    stats_enter!(1),
     r#"
         mrs      r0, cpsr                 // Load processor status
         tst      r0, {t_bit}              // Occurred in Thumb state?
@@ -264,10 +655,204 @@ core::arch::global_asm!(
         // r0 now contains SVC number
         bl       _svc_handler
     "#,
+    stats_exit!(1),
     restore_context!(),
     r#"
         rfefd   sp!
     .size _asm_svc_handler, . - _asm_svc_handler
+    "#,
+    svc_mode = const ProcessorMode::Svc as u8,
+    t_bit = const {
+        Cpsr::new_with_raw_value(0)
+            .with_t(true)
+            .raw_value()
+    },
+);
+
+/// This macro expands to code for saving an `ExceptionFrame`, once `srsfd` has
+/// saved the address of the faulting instruction and the SPSR.
+///
+/// It should match `restore_frame!`. It leaves a pointer to the frame in r4.
+#[cfg(any(arm_architecture = "v7-r", arm_architecture = "v8-r"))]
+macro_rules! save_frame {
+    () => {
+        r#"
+        // save r0-r12, leaving room for SP and LR above them
+        sub     sp, sp, 60
+        stmia   sp, {{r0-r12}}
+        // briefly switch to the mode we came from, to fetch its r8-r12 (which
+        // FIQ mode banks), SP and LR (User mode shares those with System
+        // mode, which we can leave)
+        mrs     r0, spsr
+        and     r0, r0, {mode_bits}
+        cmp     r0, {usr_mode}
+        moveq   r0, {sys_mode}
+        mrs     r1, cpsr
+        bic     r2, r1, {mode_bits}
+        orr     r2, r2, r0
+        add     r3, sp, 32
+        msr     cpsr_c, r2
+        stmia   r3!, {{r8-r12}}
+        mov     r0, sp
+        mov     r2, lr
+        stmia   r3, {{r0, r2}}
+        msr     cpsr_c, r1
+        // keep a pointer to the frame in a preserved register
+        mov     r4, sp
+        "#
+    };
+}
+
+/// This macro expands to code for restoring the registers in an
+/// `ExceptionFrame` and discarding it, leaving the return address and SPSR for
+/// `rfefd`.
+///
+/// It should match `save_frame!`.
+#[cfg(any(arm_architecture = "v7-r", arm_architecture = "v8-r"))]
+macro_rules! restore_frame {
+    () => {
+        r#"
+        // briefly switch to the mode we came from, to restore its r8-r12
+        ldr     r0, [sp, 64]
+        and     r0, r0, {mode_bits}
+        cmp     r0, {usr_mode}
+        moveq   r0, {sys_mode}
+        mrs     r1, cpsr
+        bic     r2, r1, {mode_bits}
+        orr     r2, r2, r0
+        add     r3, sp, 32
+        msr     cpsr_c, r2
+        ldmia   r3, {{r8-r12}}
+        msr     cpsr_c, r1
+        // restore r0-r7, and skip over the rest
+        ldmia   sp, {{r0-r7}}
+        add     sp, sp, 60
+        "#
+    };
+}
+
+// Our assembly language Undefined, Prefetch Abort and Data Abort handlers.
+//
+// Each saves an `ExceptionFrame` (r0-r12, SP, LR, the faulting PC and the
+// SPSR) on the stack of the mode it runs in, and calls a C-compatible handler
+// like `extern "C" fn handler(frame: &ExceptionFrame) -> usize;` with a
+// pointer to it. The address the handler returns goes in the frame's PC, which
+// is where we resume.
+#[cfg(any(arm_architecture = "v7-r", arm_architecture = "v8-r"))]
+core::arch::global_asm!(
+    r#"
+    .section .text.handlers
+    // Work around https://github.com/rust-lang/rust/issues/127269
+    .fpu vfp3-d16
+    .align 0
+
+    // Called from the vector table when we have an Undefined Instruction
+    // exception. Saves state and calls `_undefined_handler`.
+    .global _asm_default_undefined_handler
+    .type _asm_default_undefined_handler, %function
+    _asm_default_undefined_handler:
+        srsfd   sp!, {und_mode}
+    "#,
+    save_frame!(),
+    r#"
+        // LR was 4 bytes on from the undefined instruction in Arm state, and
+        // 2 bytes on in Thumb state
+        ldr     r0, [sp, 64]
+        ldr     r1, [sp, 60]
+        tst     r0, {t_bit}
+        subeq   r1, r1, 4
+        subne   r1, r1, 2
+        str     r1, [sp, 60]
+    "#,
+    save_context!(),
+    stats_enter!(0),
+    r#"
+        // call the C handler with the frame, and resume where it says
+        mov     r0, r4
+        bl      _undefined_handler
+        str     r0, [r4, 60]
+    "#,
+    stats_exit!(0),
+    restore_context!(),
+    restore_frame!(),
+    r#"
+        // return to the address the handler gave us
+        rfefd   sp!
+    .size _asm_default_undefined_handler, . - _asm_default_undefined_handler
+
+    // Called from the vector table when we have a Prefetch Abort.
+    // Saves state and calls `_prefetch_handler`.
+    .global _asm_default_prefetch_handler
+    .type _asm_default_prefetch_handler, %function
+    _asm_default_prefetch_handler:
+        sub     lr, lr, 4
+        srsfd   sp!, {abt_mode}
+    "#,
+    save_frame!(),
+    save_context!(),
+    stats_enter!(2),
+    r#"
+        // call the C handler with the frame, and resume where it says
+        mov     r0, r4
+        bl      _prefetch_handler
+        str     r0, [r4, 60]
+    "#,
+    stats_exit!(2),
+    restore_context!(),
+    restore_frame!(),
+    r#"
+        // return to the address the handler gave us
+        rfefd   sp!
+    .size _asm_default_prefetch_handler, . - _asm_default_prefetch_handler
+
+    // Called from the vector table when we have a Data Abort.
+    // Saves state and calls `_abort_handler`.
+    .global _asm_default_abort_handler
+    .type _asm_default_abort_handler, %function
+    _asm_default_abort_handler:
+        sub     lr, lr, 8
+        srsfd   sp!, {abt_mode}
+    "#,
+    save_frame!(),
+    save_context!(),
+    stats_enter!(3),
+    r#"
+        // call the C handler with the frame, and resume where it says
+        mov     r0, r4
+        bl      _abort_handler
+        str     r0, [r4, 60]
+    "#,
+    stats_exit!(3),
+    restore_context!(),
+    restore_frame!(),
+    r#"
+        // return to the address the handler gave us
+        rfefd   sp!
+    .size _asm_default_abort_handler, . - _asm_default_abort_handler
+    "#,
+    und_mode = const ProcessorMode::Und as u8,
+    abt_mode = const ProcessorMode::Abt as u8,
+    usr_mode = const ProcessorMode::Usr as u8,
+    sys_mode = const ProcessorMode::Sys as u8,
+    mode_bits = const 0x1F,
+    t_bit = const {
+        Cpsr::new_with_raw_value(0)
+            .with_t(true)
+            .raw_value()
+    },
+);
+
+// Our assembly language IRQ handler, which runs `_irq_handler` in IRQ mode
+#[cfg(all(
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
+    not(feature = "nested-interrupts")
+))]
+core::arch::global_asm!(
+    r#"
+    .section .text.handlers
+    // Work around https://github.com/rust-lang/rust/issues/127269
+    .fpu vfp3-d16
+    .align 0
 
     // Called from the vector table when we have an interrupt.
     // Saves state and calls a C-compatible handler like
@@ -279,22 +864,178 @@ core::arch::global_asm!(
         srsfd   sp!, {irq_mode}
     "#,
     save_context!(),
+    stats_enter!(4),
     r#"
         // call C handler
         bl      _irq_handler
     "#,
+    stats_exit!(4),
     restore_context!(),
     r#"
         rfefd   sp!
     .size _asm_irq_handler, . - _asm_irq_handler
     "#,
-    svc_mode = const ProcessorMode::Svc as u8,
     irq_mode = const ProcessorMode::Irq as u8,
-    t_bit = const {
-        Cpsr::new_with_raw_value(0)
-            .with_t(true)
-            .raw_value()
-    },
+);
+
+// Our assembly language IRQ handler, which runs `_irq_handler` in System mode
+// so that it can be interrupted.
+//
+// Once LR_irq and SPSR_irq are on the System mode stack, another IRQ can
+// overwrite them without harm. We call the handler with IRQs still masked,
+// and leave it to unmask them once the interrupt controller has raised its
+// running priority (i.e. after acknowledging the interrupt).
+#[cfg(all(
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
+    feature = "nested-interrupts"
+))]
+core::arch::global_asm!(
+    r#"
+    .section .text.handlers
+    // Work around https://github.com/rust-lang/rust/issues/127269
+    .fpu vfp3-d16
+    .align 0
+
+    // Called from the vector table when we have an interrupt.
+    // Saves state on the System mode stack and calls a C-compatible handler
+    // like `extern "C" fn irq_handler();` in System mode
+    .global _asm_irq_handler
+    .type _asm_irq_handler, %function
+    _asm_irq_handler:
+        sub     lr, lr, 4
+        // save LR_irq and SPSR_irq on the System mode stack, and switch to it
+        srsfd   sp!, {sys_mode}
+        cps     {sys_mode}
+        // save the interrupted code's LR, which our call will clobber
+        push    {{lr}}
+    "#,
+    save_context!(),
+    stats_enter!(4),
+    r#"
+        // call C handler
+        bl      _irq_handler
+        // in case the handler left IRQs unmasked
+        cpsid   i
+    "#,
+    stats_exit!(4),
+    restore_context!(),
+    r#"
+        pop     {{lr}}
+        rfefd   sp!
+    .size _asm_irq_handler, . - _asm_irq_handler
+    "#,
+    sys_mode = const ProcessorMode::Sys as u8,
+);
+
+/// This macro expands to code for saving the FPU context on entry to our FIQ
+/// handler.
+///
+/// It should match `fiq_restore_fpu!`. FPSCR and FPEXC go in the banked r8 and
+/// r9, which the handler preserves.
+#[cfg(all(
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
+    feature = "fiq-trampoline",
+    any(target_abi = "eabihf", feature = "eabi-fpu")
+))]
+macro_rules! fiq_save_fpu {
+    () => {
+        r#"
+        // save FPU context
+        vpush   {{d0-d7}}
+        vmrs    r8, FPSCR
+        vmrs    r9, FPEXC
+        "#
+    };
+}
+
+/// This macro expands to code for restoring the FPU context on exit from our
+/// FIQ handler.
+///
+/// It should match `fiq_save_fpu!`.
+#[cfg(all(
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
+    feature = "fiq-trampoline",
+    any(target_abi = "eabihf", feature = "eabi-fpu")
+))]
+macro_rules! fiq_restore_fpu {
+    () => {
+        r#"
+        // restore FPU context
+        vmsr    FPEXC, r9
+        vmsr    FPSCR, r8
+        vpop    {{d0-d7}}
+        "#
+    };
+}
+
+/// This macro expands to code that does nothing because there is no FPU
+#[cfg(all(
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
+    feature = "fiq-trampoline",
+    not(any(target_abi = "eabihf", feature = "eabi-fpu"))
+))]
+macro_rules! fiq_save_fpu {
+    () => {
+        r#"
+        // no FPU - do nothing
+        "#
+    };
+}
+
+/// This macro expands to code that does nothing because there is no FPU
+#[cfg(all(
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
+    feature = "fiq-trampoline",
+    not(any(target_abi = "eabihf", feature = "eabi-fpu"))
+))]
+macro_rules! fiq_restore_fpu {
+    () => {
+        r#"
+        // no FPU - do nothing
+        "#
+    };
+}
+
+// Our assembly language FIQ handler.
+//
+// FIQ mode has its own r8-r12, so only r0-r3 and LR need saving before we
+// call an `extern "C"` function. FIQ doesn't nest, so the FIQ stack is always
+// eight-byte aligned on entry, and we push an even number of registers to keep
+// it that way.
+#[cfg(all(
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
+    feature = "fiq-trampoline"
+))]
+core::arch::global_asm!(
+    r#"
+    .section .text.handlers
+    // Work around https://github.com/rust-lang/rust/issues/127269
+    .fpu vfp3-d16
+    .align 0
+
+    // Called from the vector table when we have a fast interrupt.
+    // Saves state and calls a C-compatible handler like
+    // `extern "C" fn fiq_handler();`
+    .global _asm_fiq_handler
+    .type _asm_fiq_handler, %function
+    _asm_fiq_handler:
+        sub     lr, lr, 4
+        // r12 is banked, but pushing it keeps the stack aligned
+        push    {{r0-r3, r12, lr}}
+    "#,
+    fiq_save_fpu!(),
+    stats_enter!(5),
+    r#"
+        // call C handler
+        bl      _fiq_handler
+    "#,
+    stats_exit!(5),
+    fiq_restore_fpu!(),
+    r#"
+        // return, restoring CPSR from SPSR_fiq
+        ldmfd   sp!, {{r0-r3, r12, pc}}^
+    .size _asm_fiq_handler, . - _asm_fiq_handler
+    "#
 );
 
 /// This macro expands to code to turn on the FPU
@@ -329,6 +1070,231 @@ macro_rules! fpu_enable {
     };
 }
 
+/// This macro expands to code to program and enable the MPU
+#[cfg(all(
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
+    feature = "memory-protection"
+))]
+macro_rules! mpu_init {
+    () => {
+        r#"
+        // Program the MPU from the linker symbols
+        bl      _mpu_init
+        "#
+    };
+}
+
+/// This macro expands to code that does nothing because memory protection is
+/// not enabled
+#[cfg(all(
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
+    not(feature = "memory-protection")
+))]
+macro_rules! mpu_init {
+    () => {
+        r#"
+        // no memory protection - do nothing
+        "#
+    };
+}
+
+/// This macro expands to code to invalidate and enable the caches
+#[cfg(all(
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
+    any(feature = "icache", feature = "dcache", feature = "branch-prediction")
+))]
+macro_rules! cache_init {
+    () => {
+        r#"
+        // Invalidate and enable the caches and branch prediction
+        bl      _cache_init
+        "#
+    };
+}
+
+/// This macro expands to code that does nothing because no caches were
+/// selected
+#[cfg(all(
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
+    not(any(feature = "icache", feature = "dcache", feature = "branch-prediction"))
+))]
+macro_rules! cache_init {
+    () => {
+        r#"
+        // no caches - do nothing
+        "#
+    };
+}
+
+/// This macro expands to code to leave a gap below a stack, for the MPU to
+/// guard
+#[cfg(all(
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
+    feature = "stack-guard"
+))]
+macro_rules! stack_guard {
+    () => {
+        r#"
+        // Skip over the stack guard
+        ldr     r1, =_stack_guard_size
+        sub     r0, r0, r1
+        "#
+    };
+}
+
+/// This macro expands to code that does nothing because there are no stack
+/// guards
+#[cfg(all(
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
+    not(feature = "stack-guard")
+))]
+macro_rules! stack_guard {
+    () => {
+        r#"
+        // no stack guards - do nothing
+        "#
+    };
+}
+
+/// This macro expands to code to find and initialise the GIC
+#[cfg(all(arm_architecture = "v8-r", feature = "gic"))]
+macro_rules! gic_init {
+    () => {
+        r#"
+        // Find and initialise the GIC, and hand it to the IRQ dispatcher
+        bl      _gic_init
+        "#
+    };
+}
+
+/// This macro expands to code that does nothing because the GIC is left to the
+/// application
+#[cfg(all(
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
+    not(all(arm_architecture = "v8-r", feature = "gic"))
+))]
+macro_rules! gic_init {
+    () => {
+        r#"
+        // no GIC initialisation - do nothing
+        "#
+    };
+}
+
+/// This macro expands to code to make IRQs branch straight to the handler the
+/// VIC gives us
+#[cfg(all(arm_architecture = "v7-r", feature = "vectored-interrupts"))]
+macro_rules! vic_enable {
+    () => {
+        r#"
+        // Set SCTLR.VE (bit 24, as checked below)
+        mrc     p15, 0, r0, c1, c0, 0
+        orr     r0, r0, #(1 << 24)
+        mcr     p15, 0, r0, c1, c0, 0
+        isb
+        "#
+    };
+}
+
+// `vic_enable!` hard-codes the position of SCTLR.VE, so check it matches
+#[cfg(all(arm_architecture = "v7-r", feature = "vectored-interrupts"))]
+const _: () = assert!(
+    cortex_r::register::Sctlr::new_with_raw_value(0)
+        .with_ve(true)
+        .raw_value()
+        == 1 << 24
+);
+
+/// This macro expands to code that does nothing because vectored interrupts are
+/// not enabled
+#[cfg(all(
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
+    not(all(arm_architecture = "v7-r", feature = "vectored-interrupts"))
+))]
+macro_rules! vic_enable {
+    () => {
+        r#"
+        // no vectored interrupts - do nothing
+        "#
+    };
+}
+
+/// This macro expands to code to start the PMU cycle counter
+#[cfg(all(
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
+    feature = "stats"
+))]
+macro_rules! stats_init {
+    () => {
+        r#"
+        // Start the cycle counter, for timing exceptions
+        bl      _stats_init
+        "#
+    };
+}
+
+/// This macro expands to code that does nothing because statistics are not
+/// enabled
+#[cfg(all(
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
+    not(feature = "stats")
+))]
+macro_rules! stats_init {
+    () => {
+        r#"
+        // no stats - do nothing
+        "#
+    };
+}
+
+/// Make an assembly language trampoline for a vectored IRQ handler
+///
+/// With the `vectored-interrupts` feature, the core branches straight to the
+/// address the VIC supplies, in IRQ mode, so every handler needs its own
+/// trampoline to save state and return from the exception. Writing
+/// `vectored_interrupt!(uart_trampoline => uart_handler);` defines a global
+/// function called `uart_trampoline` which calls an
+/// `extern "C" fn uart_handler()`. Give `uart_trampoline` to
+/// `cortex_r::vic::Vic::set_vector`.
+///
+/// The handler must tell the VIC it has finished (with
+/// `cortex_r::vic::Vic::end_interrupt`) before it returns.
+#[cfg(arm_architecture = "v7-r")]
+#[macro_export]
+macro_rules! vectored_interrupt {
+    ($trampoline:ident => $handler:path) => {
+        extern "C" {
+            /// A trampoline for a vectored IRQ handler
+            pub fn $trampoline();
+        }
+
+        core::arch::global_asm!(
+            concat!(
+                ".section .text.", stringify!($trampoline), "\n",
+                ".fpu vfp3-d16\n",
+                ".align 0\n",
+                ".global ", stringify!($trampoline), "\n",
+                ".type ", stringify!($trampoline), ", %function\n",
+                stringify!($trampoline), ":\n",
+                "    sub     lr, lr, 4\n",
+                "    srsfd   sp!, {irq_mode}\n",
+            ),
+            $crate::save_context!(),
+            r#"
+                // call C handler
+                bl      {handler}
+            "#,
+            $crate::restore_context!(),
+            concat!(
+                "    rfefd   sp!\n",
+                ".size ", stringify!($trampoline), ", . - ", stringify!($trampoline), "\n",
+            ),
+            irq_mode = const $crate::__ProcessorMode::Irq as u8,
+            handler = sym $handler,
+        );
+    };
+}
+
 // Start-up code for Armv7-R (and Armv8-R once we've left EL2)
 //
 // We set up our stacks and `kmain` in system mode.
@@ -348,16 +1314,41 @@ core::arch::global_asm!(
         mov     sp, r0
         ldr     r1, =_fiq_stack_size
         sub     r0, r0, r1
+    "#,
+    stack_guard!(),
+    r#"
         // Set stack pointer (right after) and mask interrupts for for IRQ mode (Mode 0x12)
         msr     cpsr, {irq_mode}
         mov     sp, r0
         ldr     r1, =_irq_stack_size
         sub     r0, r0, r1
+    "#,
+    stack_guard!(),
+    r#"
         // Set stack pointer (right after) and mask interrupts for for SVC mode (Mode 0x13)
         msr     cpsr, {svc_mode}
         mov     sp, r0
         ldr     r1, =_svc_stack_size
         sub     r0, r0, r1
+    "#,
+    stack_guard!(),
+    r#"
+        // Set stack pointer (right after) and mask interrupts for for Abort mode (Mode 0x17)
+        msr     cpsr, {abt_mode}
+        mov     sp, r0
+        ldr     r1, =_abt_stack_size
+        sub     r0, r0, r1
+    "#,
+    stack_guard!(),
+    r#"
+        // Set stack pointer (right after) and mask interrupts for for Undefined mode (Mode 0x1B)
+        msr     cpsr, {und_mode}
+        mov     sp, r0
+        ldr     r1, =_und_stack_size
+        sub     r0, r0, r1
+    "#,
+    stack_guard!(),
+    r#"
         // Set stack pointer (right after) and mask interrupts for for System mode (Mode 0x1F)
         msr     cpsr, {sys_mode}
         mov     sp, r0
@@ -389,6 +1380,13 @@ core::arch::global_asm!(
         stm     r0!, {{r3}}
         b       0b
     1:
+    "#,
+    mpu_init!(),
+    cache_init!(),
+    gic_init!(),
+    vic_enable!(),
+    stats_init!(),
+    r#"
         // Jump to application
         bl      kmain
         // In case the application returns, loop forever
@@ -416,6 +1414,20 @@ core::arch::global_asm!(
             .with_f(true)
             .raw_value()
     },
+    abt_mode = const {
+        Cpsr::new_with_raw_value(0)
+            .with_mode(ProcessorMode::Abt)
+            .with_i(true)
+            .with_f(true)
+            .raw_value()
+    },
+    und_mode = const {
+        Cpsr::new_with_raw_value(0)
+            .with_mode(ProcessorMode::Und)
+            .with_i(true)
+            .with_f(true)
+            .raw_value()
+    },
     sys_mode = const {
         Cpsr::new_with_raw_value(0)
             .with_mode(ProcessorMode::Sys)
@@ -0,0 +1,262 @@
+//! Programs the MPU from the memory layout in the linker script
+//!
+//! This is used by the `memory-protection` feature. It runs after `.data` and
+//! `.bss` have been initialised, and before `kmain` is called.
+//!
+//! Code (`.text` and `.rodata`) is made read-only and executable, the DATA
+//! region is made read/write and Execute Never, and a guard region is placed
+//! over the bottom of the address space to catch null pointer dereferences.
+//! With the `stack-guard` feature, a guard region is also placed below each of
+//! the stacks. Guard regions are no-access on Armv7-R. PMSAv8 cannot deny
+//! privileged reads, so on Armv8-R the stack guards are read-only (writes
+//! fault), and the null guard is covered by two regions, as any access which
+//! hits two regions faults. Everything else (e.g. peripherals) is left to the
+//! default memory map, as a background region for privileged accesses.
+
+#[cfg(feature = "stack-guard")]
+use crate::stack_guard::Stack;
+
+extern "C" {
+    static __stext: u8;
+    static __ecode: u8;
+    static __sram: u8;
+    static __eram: u8;
+    static _null_guard_size: u8;
+}
+
+/// The memory layout, as described by the linker script
+struct Layout {
+    /// The start of `.text`
+    code_start: u32,
+    /// The end of `.rodata`, padded out to suit the MPU
+    code_end: u32,
+    /// The start of the DATA region
+    ram_start: u32,
+    /// The end of the DATA region
+    ram_end: u32,
+    /// The size of the guard region at address zero
+    null_guard_size: u32,
+}
+
+impl Layout {
+    /// Get the memory layout from the linker symbols
+    fn get() -> Layout {
+        Layout {
+            code_start: core::ptr::addr_of!(__stext) as u32,
+            code_end: core::ptr::addr_of!(__ecode) as u32,
+            ram_start: core::ptr::addr_of!(__sram) as u32,
+            ram_end: core::ptr::addr_of!(__eram) as u32,
+            null_guard_size: core::ptr::addr_of!(_null_guard_size) as u32,
+        }
+    }
+
+    /// Can we place a null guard region?
+    ///
+    /// Not if it is disabled, or if the code or RAM starts inside it.
+    fn has_null_guard(&self) -> bool {
+        self.null_guard_size != 0
+            && self.code_start >= self.null_guard_size
+            && self.ram_start >= self.null_guard_size
+    }
+}
+
+/// The stacks to guard on Armv7-R, most important first
+///
+/// A Cortex-R4 may have only 8 MPU regions. Once the DATA, code and null guard
+/// regions are in place, that leaves room for five stack guards, so the FIQ
+/// stack goes unguarded.
+#[cfg(all(arm_architecture = "v7-r", feature = "stack-guard"))]
+const GUARD_PRIORITY: [Stack; 6] = [
+    Stack::Sys,
+    Stack::Irq,
+    Stack::Svc,
+    Stack::Abt,
+    Stack::Und,
+    Stack::Fiq,
+];
+
+/// Program and enable the PMSAv7 MPU
+#[cfg(arm_architecture = "v7-r")]
+#[no_mangle]
+extern "C" fn _mpu_init() {
+    use cortex_r::mpu::v7::{AccessPermissions, CachePolicy, MemAttr, Mpu, Region, RegionSize};
+
+    const NORMAL: MemAttr = MemAttr::Normal {
+        outer: CachePolicy::WriteBackWriteAllocate,
+        inner: CachePolicy::WriteBackWriteAllocate,
+        shareable: false,
+    };
+
+    /// The smallest region size that holds `bytes`
+    ///
+    /// Every `u32` fits in the largest (4 GiB) region, so this never actually
+    /// has to clamp.
+    fn region_size(bytes: u32) -> RegionSize {
+        RegionSize::at_least(u64::from(bytes)).unwrap_or(RegionSize::G4)
+    }
+
+    let layout = Layout::get();
+
+    // The region is rounded up to a power of two, so switch off the
+    // subregions (eighths) which lie past the end of DATA. The linker script
+    // checks that DATA ends on a subregion boundary. Regions smaller than 256
+    // bytes have no subregions, but then DATA must be exactly a power of two.
+    let ram_len = layout.ram_end - layout.ram_start;
+    let ram_size = region_size(ram_len);
+    let ram_trim = if ram_size.bytes() >= 256 {
+        let used = u64::from(ram_len).div_ceil(ram_size.bytes() / 8);
+        !((1u16 << used) - 1) as u8
+    } else {
+        0
+    };
+    let ram = Region::new(layout.ram_start, ram_size)
+        .with_subregion_disable(ram_trim)
+        .with_mem_attr(NORMAL)
+        .with_access(AccessPermissions::ReadWrite)
+        .with_no_exec(true);
+
+    let code = Region::new(
+        layout.code_start,
+        region_size(layout.code_end - layout.code_start),
+    )
+    .with_mem_attr(NORMAL)
+    .with_access(AccessPermissions::ReadOnly)
+    .with_no_exec(false);
+
+    let null_guard = layout.has_null_guard().then(|| {
+        Region::new(0, region_size(layout.null_guard_size))
+            .with_access(AccessPermissions::NoAccess)
+            .with_no_exec(true)
+    });
+
+    #[cfg(feature = "stack-guard")]
+    let stack_guards = GUARD_PRIORITY.map(|stack| {
+        Region::new(stack.guard_base(), region_size(Stack::guard_size()))
+            .with_access(AccessPermissions::NoAccess)
+            .with_no_exec(true)
+    });
+    #[cfg(not(feature = "stack-guard"))]
+    let stack_guards: [Region; 0] = [];
+
+    // Where regions overlap, the highest numbered region wins. So the code can
+    // sit inside the DATA region, and the null guard and stack guards beat
+    // everything. Any regions the MPU has no room for are left out.
+    let mut regions = [Some(ram), Some(code), null_guard]
+        .into_iter()
+        .flatten()
+        .chain(stack_guards);
+
+    // Safety: Nothing else is using the MPU this early in start-up, and these
+    // regions cover everything that the start-up code and `kmain` rely on.
+    let mut mpu = unsafe { Mpu::new() };
+    for idx in 0..mpu.num_regions() {
+        // Neither call can fail: `idx` is in range, and the linker script
+        // checks that every region is aligned to its size.
+        let _ = match regions.next() {
+            Some(region) => mpu.set_region(idx, &region),
+            None => mpu.disable_region(idx),
+        };
+    }
+    mpu.enable_background_region();
+    mpu.enable();
+}
+
+/// Program and enable the PMSAv8 EL1 MPU
+///
+/// An access which hits two PMSAv8 regions faults, so the null guard is two
+/// identical regions. Otherwise regions must not overlap, so the DATA region is
+/// split into pieces around the code (if it lives in DATA) and the stack
+/// guards. With the code outside DATA, that needs at most 16 regions, which is
+/// the fewest a Cortex-R52 has.
+#[cfg(arm_architecture = "v8-r")]
+#[no_mangle]
+extern "C" fn _mpu_init() {
+    use cortex_r::mpu::v8::{AccessPermissions, Config, El1Mpu, MemAttr, Region};
+
+    /// The regions to program, in no particular order
+    struct Regions {
+        list: [Region; 24],
+        len: usize,
+        /// How many regions the MPU has
+        max: usize,
+    }
+
+    impl Regions {
+        /// Add a region covering `start..end`, unless that is empty
+        fn push(&mut self, start: u32, end: u32, access: AccessPermissions, no_exec: bool) {
+            if end > start && self.len < self.max {
+                self.list[self.len] = Region::new(start, end - 1)
+                    .with_access(access)
+                    .with_no_exec(no_exec)
+                    .with_mair_index(0);
+                self.len += 1;
+            }
+        }
+    }
+
+    // Safety: Nothing else is using the MPU this early in start-up, and the
+    // regions below cover everything that the start-up code and `kmain` rely
+    // on.
+    let mut mpu = unsafe { El1Mpu::new() };
+
+    let layout = Layout::get();
+    let mut regions = Regions {
+        list: [Region::new(0, 0); 24],
+        len: 0,
+        max: usize::from(mpu.num_regions()).min(24),
+    };
+
+    regions.push(
+        layout.code_start,
+        layout.code_end,
+        AccessPermissions::ReadOnly,
+        false,
+    );
+
+    // There is no 'no access' permission for privileged code in PMSAv8, so
+    // the guards are read-only and never executable. Writes to them (like a
+    // stack overflow) fault. To make null pointer reads fault too, the null
+    // guard gets a second, identical region at the end, if there is room.
+    let null_guard = layout.has_null_guard().then_some(layout.null_guard_size);
+    if let Some(size) = null_guard {
+        regions.push(0, size, AccessPermissions::PrivReadOnly, true);
+    }
+
+    // The parts of the DATA region which are not plain RAM, as `start..end`
+    let mut holes = [(0u32, 0u32); 7];
+    holes[0] = (layout.code_start, layout.code_end);
+    #[cfg(feature = "stack-guard")]
+    for (hole, stack) in holes[1..].iter_mut().zip(Stack::ALL) {
+        let guard_end = stack.guard_base() + Stack::guard_size();
+        *hole = (stack.guard_base(), guard_end);
+        regions.push(
+            stack.guard_base(),
+            guard_end,
+            AccessPermissions::PrivReadOnly,
+            true,
+        );
+    }
+    holes.sort_unstable();
+
+    // Cover whatever is left of the DATA region, between the holes
+    let mut cursor = layout.ram_start;
+    for (start, end) in holes {
+        let start = start.max(cursor).min(layout.ram_end);
+        regions.push(cursor, start, AccessPermissions::ReadWrite, true);
+        cursor = end.max(cursor).min(layout.ram_end);
+    }
+    regions.push(cursor, layout.ram_end, AccessPermissions::ReadWrite, true);
+
+    // Any access which hits both copies of the null guard faults
+    if let Some(size) = null_guard {
+        regions.push(0, size, AccessPermissions::PrivReadOnly, true);
+    }
+
+    // This cannot fail, as the linker script checks that every region is
+    // suitably aligned, and we never use more regions than the MPU has.
+    let _ = mpu.configure(&Config {
+        regions: &regions.list[..regions.len],
+        memory_attributes: &[MemAttr::NORMAL_WRITE_BACK],
+        background_config: true,
+    });
+}
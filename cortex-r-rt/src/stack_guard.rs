@@ -0,0 +1,75 @@
+//! Guard regions below each of the processor-mode stacks
+//!
+//! With the `stack-guard` feature, `_el1_start` leaves a gap of
+//! `_stack_guard_size` bytes below each stack, and `_mpu_init` makes each gap
+//! a guard region (no-access on Armv7-R; read-only on Armv8-R, where writes
+//! fault). A stack overflow therefore causes a Data Abort, instead of silently
+//! corrupting whatever lies below the stack.
+
+extern "C" {
+    static _stack_guard_size: u8;
+    static _fiq_stack_guard: u8;
+    static _irq_stack_guard: u8;
+    static _svc_stack_guard: u8;
+    static _abt_stack_guard: u8;
+    static _und_stack_guard: u8;
+    static _sys_stack_guard: u8;
+}
+
+/// One of the processor-mode stacks set up on start-up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stack {
+    /// The stack used in FIQ mode
+    Fiq,
+    /// The stack used in IRQ mode
+    Irq,
+    /// The stack used in SVC mode
+    Svc,
+    /// The stack used in Abort mode
+    Abt,
+    /// The stack used in Undefined mode
+    Und,
+    /// The stack used in System mode
+    Sys,
+}
+
+impl Stack {
+    /// All of the stacks, from the top of memory downwards
+    pub(crate) const ALL: [Stack; 6] = [
+        Stack::Fiq,
+        Stack::Irq,
+        Stack::Svc,
+        Stack::Abt,
+        Stack::Und,
+        Stack::Sys,
+    ];
+
+    /// The start address of the guard region below this stack
+    pub fn guard_base(self) -> u32 {
+        let guard = match self {
+            Stack::Fiq => core::ptr::addr_of!(_fiq_stack_guard),
+            Stack::Irq => core::ptr::addr_of!(_irq_stack_guard),
+            Stack::Svc => core::ptr::addr_of!(_svc_stack_guard),
+            Stack::Abt => core::ptr::addr_of!(_abt_stack_guard),
+            Stack::Und => core::ptr::addr_of!(_und_stack_guard),
+            Stack::Sys => core::ptr::addr_of!(_sys_stack_guard),
+        };
+        guard as u32
+    }
+
+    /// The size of the guard region below each stack, in bytes
+    pub fn guard_size() -> u32 {
+        core::ptr::addr_of!(_stack_guard_size) as u32
+    }
+}
+
+/// Work out which stack overflowed, given the address of a faulting access
+///
+/// Pass in the address from the Data Fault Address Register (DFAR) when you
+/// handle a Data Abort. Returns `None` if the address isn't in any of the stack
+/// guard regions.
+pub fn overflowed_stack(fault_address: u32) -> Option<Stack> {
+    Stack::ALL
+        .into_iter()
+        .find(|stack| fault_address.wrapping_sub(stack.guard_base()) < Stack::guard_size())
+}
@@ -0,0 +1,262 @@
+//! Counting exceptions, and timing them with the PMU cycle counter
+//!
+//! With the `stats` feature, each of our assembly language trampolines counts
+//! the exception it handles, and measures how many cycles the handler takes.
+//! The IRQ dispatcher does the same for each interrupt ID below
+//! [`TRACKED_INTERRUPTS`], and also measures its latency - the cycles from
+//! entering the IRQ trampoline to calling the interrupt's handler. When several
+//! interrupts are handled in one go, the latency of each one after the first
+//! runs from when the dispatcher went back to acknowledge it instead, so it
+//! doesn't include the handlers which ran before it.
+//!
+//! Timestamps are taken once the trampoline has saved its context, so that
+//! isn't included. The cycle counter is 32 bits wide, so anything which takes
+//! 2^32 cycles or more is mis-measured.
+
+use core::cell::RefCell;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use cortex_r::interrupt::Mutex;
+use cortex_r::pmu::cycle_count;
+
+/// The number of interrupt IDs (starting from zero) we keep statistics for
+pub const TRACKED_INTERRUPTS: usize = 128;
+
+/// The exceptions our trampolines count
+///
+/// Only the exceptions whose trampoline `cortex-r-rt` supplies are counted.
+/// The values are used by the trampolines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Exception {
+    /// An Undefined Instruction exception
+    Undefined = 0,
+    /// A Supervisor Call
+    Svc = 1,
+    /// A Prefetch Abort
+    PrefetchAbort = 2,
+    /// A Data Abort
+    DataAbort = 3,
+    /// An Interrupt Request
+    Irq = 4,
+    /// A Fast Interrupt Request
+    Fiq = 5,
+}
+
+impl Exception {
+    /// The number of kinds of exception
+    const COUNT: usize = 6;
+}
+
+/// A count of events, and how many cycles they took
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timing {
+    /// The number of events
+    pub count: u32,
+    /// The fewest cycles any event took (`u32::MAX` if there were none)
+    pub min: u32,
+    /// The most cycles any event took
+    pub max: u32,
+    /// The cycles all the events took
+    pub total: u64,
+}
+
+impl Timing {
+    const EMPTY: Timing = Timing {
+        count: 0,
+        min: u32::MAX,
+        max: 0,
+        total: 0,
+    };
+
+    /// The average number of cycles an event took, or `None` if there were
+    /// none
+    pub fn average(&self) -> Option<u32> {
+        if self.count == 0 {
+            None
+        } else {
+            Some((self.total / u64::from(self.count)) as u32)
+        }
+    }
+
+    /// Add an event which took the given number of cycles
+    fn record(&mut self, cycles: u32) {
+        self.count = self.count.wrapping_add(1);
+        self.min = self.min.min(cycles);
+        self.max = self.max.max(cycles);
+        self.total = self.total.wrapping_add(u64::from(cycles));
+    }
+}
+
+/// Statistics for one interrupt ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InterruptStats {
+    /// The cycles from entering the IRQ trampoline (or acknowledging the
+    /// interrupt, if another was handled first) to calling the handler
+    pub latency: Timing,
+    /// The cycles the handler took
+    pub duration: Timing,
+}
+
+impl InterruptStats {
+    const EMPTY: InterruptStats = InterruptStats {
+        latency: Timing::EMPTY,
+        duration: Timing::EMPTY,
+    };
+}
+
+/// All of our statistics
+struct Table {
+    exceptions: [Timing; Exception::COUNT],
+    interrupts: [InterruptStats; TRACKED_INTERRUPTS],
+}
+
+impl Table {
+    const EMPTY: Table = Table {
+        exceptions: [Timing::EMPTY; Exception::COUNT],
+        interrupts: [InterruptStats::EMPTY; TRACKED_INTERRUPTS],
+    };
+}
+
+/// Our statistics, which are only touched with IRQ and FIQ masked
+static TABLE: Mutex<RefCell<Table>> = Mutex::new(RefCell::new(Table::EMPTY));
+
+/// When we last entered the IRQ trampoline
+static IRQ_ENTRY: AtomicU32 = AtomicU32::new(0);
+
+/// Get the statistics for an exception
+///
+/// The timing covers the whole handler - for IRQs, that's every interrupt
+/// handled before returning from the exception.
+pub fn exception(exception: Exception) -> Timing {
+    cortex_r::interrupt::free_all(|cs| TABLE.borrow(cs).borrow().exceptions[exception as usize])
+}
+
+/// Get the statistics for an interrupt ID
+///
+/// Returns `None` if the ID is not below [`TRACKED_INTERRUPTS`].
+pub fn interrupt<I>(id: I) -> Option<InterruptStats>
+where
+    I: Into<u32>,
+{
+    let id = id.into() as usize;
+    cortex_r::interrupt::free_all(|cs| TABLE.borrow(cs).borrow().interrupts.get(id).copied())
+}
+
+/// Clear all the statistics
+pub fn reset() {
+    cortex_r::interrupt::free_all(|cs| *TABLE.borrow(cs).borrow_mut() = Table::EMPTY);
+}
+
+/// When we last entered the IRQ trampoline
+///
+/// Read this before unmasking IRQs, or a nested IRQ will change it.
+pub(crate) fn irq_entry() -> u32 {
+    IRQ_ENTRY.load(Ordering::Relaxed)
+}
+
+/// Record an interrupt handler which started at `start` cycles, counting its
+/// latency from `entry` cycles
+pub(crate) fn record_interrupt(id: u32, entry: u32, start: u32) {
+    let end = cycle_count();
+    cortex_r::interrupt::free_all(|cs| {
+        if let Some(stats) = TABLE
+            .borrow(cs)
+            .borrow_mut()
+            .interrupts
+            .get_mut(id as usize)
+        {
+            stats.latency.record(start.wrapping_sub(entry));
+            stats.duration.record(end.wrapping_sub(start));
+        }
+    });
+}
+
+/// Start the cycle counter, from our start-up code
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn _stats_init() {
+    cortex_r::pmu::enable_cycle_counter();
+}
+
+/// Called by a trampoline once it has saved its context
+///
+/// Returns the time, for the trampoline to give back to [`_stats_exit`].
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn _stats_enter(exception: u32) -> u32 {
+    let now = cycle_count();
+    if exception == Exception::Irq as u32 {
+        IRQ_ENTRY.store(now, Ordering::Relaxed);
+    }
+    now
+}
+
+/// Called by a trampoline when the handler has returned
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn _stats_exit(exception: u32, entry: u32) {
+    let cycles = cycle_count().wrapping_sub(entry);
+    cortex_r::interrupt::free_all(|cs| {
+        if let Some(timing) = TABLE
+            .borrow(cs)
+            .borrow_mut()
+            .exceptions
+            .get_mut(exception as usize)
+        {
+            timing.record(cycles);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_timing() {
+        let timing = Timing::EMPTY;
+        assert_eq!(timing.count, 0);
+        assert_eq!(timing.min, u32::MAX);
+        assert_eq!(timing.max, 0);
+        assert_eq!(timing.average(), None);
+    }
+
+    #[test]
+    fn record_tracks_min_max_and_total() {
+        let mut timing = Timing::EMPTY;
+        timing.record(30);
+        assert_eq!(timing.min, 30);
+        assert_eq!(timing.max, 30);
+        assert_eq!(timing.average(), Some(30));
+        timing.record(10);
+        timing.record(50);
+        assert_eq!(
+            timing,
+            Timing {
+                count: 3,
+                min: 10,
+                max: 50,
+                total: 90,
+            }
+        );
+        assert_eq!(timing.average(), Some(30));
+    }
+
+    #[test]
+    fn average_rounds_down() {
+        let mut timing = Timing::EMPTY;
+        timing.record(1);
+        timing.record(2);
+        assert_eq!(timing.average(), Some(1));
+    }
+
+    #[test]
+    fn total_is_wider_than_a_cycle_count() {
+        let mut timing = Timing::EMPTY;
+        timing.record(u32::MAX);
+        timing.record(u32::MAX);
+        assert_eq!(timing.total, 2 * u64::from(u32::MAX));
+        assert_eq!(timing.average(), Some(u32::MAX));
+    }
+}
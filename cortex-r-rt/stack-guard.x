@@ -0,0 +1,31 @@
+/*
+Extra linker script for the `stack-guard` feature.
+
+We leave a gap of `_stack_guard_size` bytes below each of the stacks, which
+the MPU protects from writes. The System stack also gets a fixed size, so that it
+has a bottom to guard.
+
+The guard size must be a power of two, of at least 64 bytes, so that it suits
+the MPU on both Armv7-R and Armv8-R. The top of the stacks, and the size of
+every stack, must be a multiple of the guard size.
+*/
+
+PROVIDE(_stack_guard_size = 64);
+PROVIDE(_sys_stack_size = 0x4000);
+
+_fiq_stack_guard = _stack_top - _fiq_stack_size - _stack_guard_size;
+_irq_stack_guard = _fiq_stack_guard - _irq_stack_size - _stack_guard_size;
+_svc_stack_guard = _irq_stack_guard - _svc_stack_size - _stack_guard_size;
+_abt_stack_guard = _svc_stack_guard - _abt_stack_size - _stack_guard_size;
+_und_stack_guard = _abt_stack_guard - _und_stack_size - _stack_guard_size;
+_sys_stack_guard = _und_stack_guard - _sys_stack_size - _stack_guard_size;
+
+ASSERT(_stack_guard_size >= 64 && (_stack_guard_size & (_stack_guard_size - 1)) == 0, "ERROR(cortex-r-rt): size of stack guard is not a power of two of at least 64 bytes");
+ASSERT(_stack_top % _stack_guard_size == 0, "ERROR(cortex-r-rt): top of stack is not aligned to the stack guard size");
+ASSERT(_fiq_stack_size % _stack_guard_size == 0, "ERROR(cortex-r-rt): size of FIQ stack is not a multiple of the stack guard size");
+ASSERT(_irq_stack_size % _stack_guard_size == 0, "ERROR(cortex-r-rt): size of IRQ stack is not a multiple of the stack guard size");
+ASSERT(_svc_stack_size % _stack_guard_size == 0, "ERROR(cortex-r-rt): size of SVC stack is not a multiple of the stack guard size");
+ASSERT(_abt_stack_size % _stack_guard_size == 0, "ERROR(cortex-r-rt): size of Abort stack is not a multiple of the stack guard size");
+ASSERT(_und_stack_size % _stack_guard_size == 0, "ERROR(cortex-r-rt): size of Undefined stack is not a multiple of the stack guard size");
+ASSERT(_sys_stack_size % _stack_guard_size == 0, "ERROR(cortex-r-rt): size of System stack is not a multiple of the stack guard size");
+ASSERT(_sys_stack_guard >= __euninit, "ERROR(cortex-r-rt): stacks overlap with static data");
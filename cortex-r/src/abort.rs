@@ -0,0 +1,440 @@
+//! Data Aborts, Prefetch Aborts and asynchronous aborts
+//!
+//! The fault status codes are different on Armv7-R (which uses the
+//! short-descriptor format of DFSR and IFSR) and Armv8-R (which uses the
+//! long-descriptor format), so we decode them into a common [`Fault`].
+//!
+//! An asynchronous abort (called an SError on Armv8-R) is usually a bus error
+//! on a write, reported some time after the write was buffered. It is only
+//! taken when the CPSR A bit is clear. Use [`synchronize_async`] around bus
+//! transactions to make sure any such abort is taken at a known point.
+
+use core::sync::atomic::{compiler_fence, Ordering};
+
+use crate::register::{Cpsr, Dfar, Dfsr, Ifar, Ifsr};
+
+/// The kind of fault which caused an abort
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FaultKind {
+    /// The address was not in any MPU region, and the background region does
+    /// not apply
+    Background,
+    /// The MPU region does not allow this kind of access
+    Permission,
+    /// The address was not suitably aligned
+    Alignment,
+    /// A debug event occurred
+    Debug,
+    /// The memory system returned an error, synchronously
+    SyncExternal,
+    /// The memory system returned an error, some time after the access
+    AsyncExternal,
+    /// A parity or ECC error was detected, synchronously
+    SyncParity,
+    /// A parity or ECC error was detected, some time after the access
+    AsyncParity,
+    /// Some other fault status code, given here in raw form
+    Other(u8),
+}
+
+impl FaultKind {
+    /// Was this fault reported some time after the access which caused it?
+    ///
+    /// If so, the fault address is unknown.
+    pub const fn is_async(self) -> bool {
+        matches!(self, FaultKind::AsyncExternal | FaultKind::AsyncParity)
+    }
+
+    /// Was this fault an error returned by the memory system?
+    pub const fn is_external(self) -> bool {
+        matches!(self, FaultKind::SyncExternal | FaultKind::AsyncExternal)
+    }
+
+    /// Decode a five-bit short-descriptor Fault Status code
+    #[cfg(arm_architecture = "v7-r")]
+    const fn decode(fs: u8) -> FaultKind {
+        match fs {
+            0b00000 => FaultKind::Background,
+            0b00001 => FaultKind::Alignment,
+            0b00010 => FaultKind::Debug,
+            0b01000 => FaultKind::SyncExternal,
+            0b01101 => FaultKind::Permission,
+            0b10110 => FaultKind::AsyncExternal,
+            0b11000 => FaultKind::AsyncParity,
+            0b11001 => FaultKind::SyncParity,
+            other => FaultKind::Other(other),
+        }
+    }
+
+    /// Decode a six-bit long-descriptor Fault Status code
+    #[cfg(arm_architecture = "v8-r")]
+    const fn decode(status: u8) -> FaultKind {
+        match status {
+            0b000000..=0b000011 => FaultKind::Background,
+            0b001100..=0b001111 => FaultKind::Permission,
+            0b010000 => FaultKind::SyncExternal,
+            0b010001 => FaultKind::AsyncExternal,
+            0b011000 => FaultKind::SyncParity,
+            0b011001 => FaultKind::AsyncParity,
+            0b100001 => FaultKind::Alignment,
+            0b100010 => FaultKind::Debug,
+            other => FaultKind::Other(other),
+        }
+    }
+}
+
+/// The kind of error a bus returned for an external abort
+///
+/// This is how the Cortex-R5 and Cortex-R52 report the AXI response in the
+/// ExT bit of DFSR and IFSR. Other processors may use it differently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BusError {
+    /// The address did not decode to any device (DECERR)
+    Decode,
+    /// The device returned an error (SLVERR)
+    Slave,
+}
+
+/// The cause of a Data Abort or Prefetch Abort, as read from DFSR and DFAR, or
+/// IFSR and IFAR
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Fault {
+    /// The kind of fault
+    pub kind: FaultKind,
+    /// The address which was accessed, if known
+    pub address: Option<u32>,
+    /// Was the access a write? Always `false` for a Prefetch Abort.
+    pub write: bool,
+    /// For external aborts, the kind of error the bus returned
+    pub bus_error: Option<BusError>,
+}
+
+impl Fault {
+    /// Read the cause of the most recent Data Abort
+    pub fn read_data_abort() -> Fault {
+        Fault::from_data_abort(Dfsr::read(), Dfar::read())
+    }
+
+    /// Decode the cause of a Data Abort from the given register values
+    #[cfg(arm_architecture = "v7-r")]
+    pub fn from_data_abort(dfsr: Dfsr, dfar: Dfar) -> Fault {
+        let kind = FaultKind::decode(dfsr.fs().value());
+        let address_valid = !kind.is_async() && kind != FaultKind::Debug;
+        Fault {
+            kind,
+            address: address_valid.then_some(dfar.0),
+            write: dfsr.wnr(),
+            bus_error: kind.is_external().then_some(BusError::from_ext(dfsr.ext())),
+        }
+    }
+
+    /// Decode the cause of a Data Abort from the given register values
+    #[cfg(arm_architecture = "v8-r")]
+    pub fn from_data_abort(dfsr: Dfsr, dfar: Dfar) -> Fault {
+        let kind = FaultKind::decode(dfsr.status().value());
+        let address_valid = !kind.is_async() && !dfsr.fnv();
+        Fault {
+            kind,
+            address: address_valid.then_some(dfar.0),
+            write: dfsr.wnr(),
+            bus_error: kind.is_external().then_some(BusError::from_ext(dfsr.ext())),
+        }
+    }
+
+    /// Read the cause of the most recent Prefetch Abort
+    pub fn read_prefetch_abort() -> Fault {
+        Fault::from_prefetch_abort(Ifsr::read(), Ifar::read())
+    }
+
+    /// Decode the cause of a Prefetch Abort from the given register values
+    #[cfg(arm_architecture = "v7-r")]
+    pub fn from_prefetch_abort(ifsr: Ifsr, ifar: Ifar) -> Fault {
+        let kind = FaultKind::decode(ifsr.fs().value());
+        let address_valid = kind != FaultKind::Debug;
+        Fault {
+            kind,
+            address: address_valid.then_some(ifar.0),
+            write: false,
+            bus_error: kind.is_external().then_some(BusError::from_ext(ifsr.ext())),
+        }
+    }
+
+    /// Decode the cause of a Prefetch Abort from the given register values
+    #[cfg(arm_architecture = "v8-r")]
+    pub fn from_prefetch_abort(ifsr: Ifsr, ifar: Ifar) -> Fault {
+        let kind = FaultKind::decode(ifsr.status().value());
+        let address_valid = kind != FaultKind::Debug;
+        Fault {
+            kind,
+            address: address_valid.then_some(ifar.0),
+            write: false,
+            bus_error: kind.is_external().then_some(BusError::from_ext(ifsr.ext())),
+        }
+    }
+}
+
+impl BusError {
+    /// Decode the ExT bit of DFSR or IFSR
+    const fn from_ext(ext: bool) -> BusError {
+        if ext {
+            BusError::Slave
+        } else {
+            BusError::Decode
+        }
+    }
+}
+
+/// Mask asynchronous aborts
+///
+/// Any asynchronous abort which occurs stays pending until they are unmasked
+/// again.
+#[inline]
+pub fn mask_async() {
+    // Safety: We're atomically setting a bit in a special register
+    #[cfg(target_arch = "arm")]
+    unsafe {
+        core::arch::asm!("cpsid a", options(nomem, nostack, preserves_flags));
+    };
+    compiler_fence(Ordering::SeqCst);
+}
+
+/// Unmask asynchronous aborts
+///
+/// If an asynchronous abort is pending, it is taken immediately.
+///
+/// # Safety
+///
+/// Your Data Abort handler must be ready to handle an asynchronous abort.
+#[inline]
+pub unsafe fn unmask_async() {
+    compiler_fence(Ordering::SeqCst);
+    // Safety: We're atomically clearing a bit in a special register, and we're
+    // in an unsafe function that places restrictions on when you can call it
+    #[cfg(target_arch = "arm")]
+    unsafe {
+        core::arch::asm!("cpsie a", "isb", options(nomem, nostack, preserves_flags));
+    };
+}
+
+/// Are asynchronous aborts currently masked?
+#[inline]
+pub fn is_async_masked() -> bool {
+    Cpsr::read().a()
+}
+
+/// Run with asynchronous aborts masked
+///
+/// They are unmasked afterwards, if they were unmasked before.
+#[inline]
+pub fn with_async_masked<F, T>(f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    let was_masked = is_async_masked();
+    mask_async();
+    let result = f();
+    if !was_masked {
+        // Safety: We're only unmasking them if they were unmasked previously
+        unsafe {
+            unmask_async();
+        }
+    }
+    result
+}
+
+/// Run with asynchronous aborts unmasked
+///
+/// They are masked afterwards, if they were masked before.
+///
+/// # Safety
+///
+/// Your Data Abort handler must be ready to handle an asynchronous abort.
+#[inline]
+pub unsafe fn with_async_unmasked<F, T>(f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    let was_masked = is_async_masked();
+    // Safety: passed on to our caller
+    unsafe {
+        unmask_async();
+    }
+    let result = f();
+    if was_masked {
+        mask_async();
+    }
+    result
+}
+
+/// Take any asynchronous abort caused by earlier memory accesses, now
+///
+/// This waits for all outstanding memory accesses to complete, and then
+/// briefly unmasks asynchronous aborts so that any which are pending are taken
+/// here, rather than at some unknown point later on.
+///
+/// # Safety
+///
+/// Your Data Abort handler must be ready to handle an asynchronous abort.
+#[inline]
+pub unsafe fn synchronize_async() {
+    crate::asm::dsb();
+    // Safety: passed on to our caller
+    unsafe {
+        with_async_unmasked(|| {});
+    }
+}
+
+/// Is an asynchronous abort pending?
+///
+/// This reads the A bit of ISR, so it works even while they are masked.
+#[cfg(arm_architecture = "v8-r")]
+#[inline]
+pub fn is_async_pending() -> bool {
+    crate::register::Isr::read().a()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fault_kind_classes() {
+        assert!(FaultKind::AsyncExternal.is_async());
+        assert!(FaultKind::AsyncParity.is_async());
+        assert!(!FaultKind::SyncExternal.is_async());
+        assert!(FaultKind::SyncExternal.is_external());
+        assert!(FaultKind::AsyncExternal.is_external());
+        assert!(!FaultKind::SyncParity.is_external());
+        assert!(!FaultKind::Permission.is_external());
+    }
+
+    #[cfg(arm_architecture = "v7-r")]
+    #[test]
+    fn short_descriptor_fault_kinds() {
+        assert_eq!(FaultKind::decode(0b00000), FaultKind::Background);
+        assert_eq!(FaultKind::decode(0b00001), FaultKind::Alignment);
+        assert_eq!(FaultKind::decode(0b00010), FaultKind::Debug);
+        assert_eq!(FaultKind::decode(0b01000), FaultKind::SyncExternal);
+        assert_eq!(FaultKind::decode(0b01101), FaultKind::Permission);
+        assert_eq!(FaultKind::decode(0b10110), FaultKind::AsyncExternal);
+        assert_eq!(FaultKind::decode(0b11000), FaultKind::AsyncParity);
+        assert_eq!(FaultKind::decode(0b11001), FaultKind::SyncParity);
+        assert_eq!(FaultKind::decode(0b00011), FaultKind::Other(0b00011));
+    }
+
+    #[cfg(arm_architecture = "v7-r")]
+    #[test]
+    fn short_descriptor_data_abort() {
+        // A write to a region without permission: WnR, FS = 0b01101
+        let fault = Fault::from_data_abort(Dfsr::new_with_raw_value(0x80D), Dfar(0x1000));
+        assert_eq!(
+            fault,
+            Fault {
+                kind: FaultKind::Permission,
+                address: Some(0x1000),
+                write: true,
+                bus_error: None,
+            }
+        );
+        // A read which got SLVERR: ExT, FS = 0b01000
+        let fault = Fault::from_data_abort(Dfsr::new_with_raw_value(0x1008), Dfar(0x2000));
+        assert_eq!(fault.kind, FaultKind::SyncExternal);
+        assert_eq!(fault.address, Some(0x2000));
+        assert!(!fault.write);
+        assert_eq!(fault.bus_error, Some(BusError::Slave));
+        // An asynchronous DECERR: FS[4] is bit 10, FS = 0b10110
+        let fault = Fault::from_data_abort(Dfsr::new_with_raw_value(0x406), Dfar(0x3000));
+        assert_eq!(fault.kind, FaultKind::AsyncExternal);
+        assert_eq!(fault.address, None);
+        assert_eq!(fault.bus_error, Some(BusError::Decode));
+        // A watchpoint: DFAR is not valid
+        let fault = Fault::from_data_abort(Dfsr::new_with_raw_value(0x002), Dfar(0x4000));
+        assert_eq!(fault.kind, FaultKind::Debug);
+        assert_eq!(fault.address, None);
+    }
+
+    #[cfg(arm_architecture = "v7-r")]
+    #[test]
+    fn short_descriptor_prefetch_abort() {
+        let fault = Fault::from_prefetch_abort(Ifsr::new_with_raw_value(0x000), Ifar(0x5000));
+        assert_eq!(
+            fault,
+            Fault {
+                kind: FaultKind::Background,
+                address: Some(0x5000),
+                write: false,
+                bus_error: None,
+            }
+        );
+        let fault = Fault::from_prefetch_abort(Ifsr::new_with_raw_value(0x002), Ifar(0x5000));
+        assert_eq!(fault.kind, FaultKind::Debug);
+        assert_eq!(fault.address, None);
+    }
+
+    #[cfg(arm_architecture = "v8-r")]
+    #[test]
+    fn long_descriptor_fault_kinds() {
+        for level in 0..=3 {
+            assert_eq!(FaultKind::decode(level), FaultKind::Background);
+            assert_eq!(FaultKind::decode(0b001100 | level), FaultKind::Permission);
+        }
+        assert_eq!(FaultKind::decode(0b010000), FaultKind::SyncExternal);
+        assert_eq!(FaultKind::decode(0b010001), FaultKind::AsyncExternal);
+        assert_eq!(FaultKind::decode(0b011000), FaultKind::SyncParity);
+        assert_eq!(FaultKind::decode(0b011001), FaultKind::AsyncParity);
+        assert_eq!(FaultKind::decode(0b100001), FaultKind::Alignment);
+        assert_eq!(FaultKind::decode(0b100010), FaultKind::Debug);
+        assert_eq!(FaultKind::decode(0b110000), FaultKind::Other(0b110000));
+    }
+
+    #[cfg(arm_architecture = "v8-r")]
+    #[test]
+    fn long_descriptor_data_abort() {
+        // A write to a region without permission: WnR, LPAE, status = 0b001101
+        let fault = Fault::from_data_abort(Dfsr::new_with_raw_value(0xA0D), Dfar(0x1000));
+        assert_eq!(
+            fault,
+            Fault {
+                kind: FaultKind::Permission,
+                address: Some(0x1000),
+                write: true,
+                bus_error: None,
+            }
+        );
+        // A read which got SLVERR: ExT, LPAE, status = 0b010000
+        let fault = Fault::from_data_abort(Dfsr::new_with_raw_value(0x1210), Dfar(0x2000));
+        assert_eq!(fault.kind, FaultKind::SyncExternal);
+        assert_eq!(fault.address, Some(0x2000));
+        assert!(!fault.write);
+        assert_eq!(fault.bus_error, Some(BusError::Slave));
+        // The same, but with FnV set, so DFAR is not valid
+        let fault = Fault::from_data_abort(Dfsr::new_with_raw_value(0x1_1210), Dfar(0x2000));
+        assert_eq!(fault.address, None);
+        // An asynchronous DECERR: status = 0b010001
+        let fault = Fault::from_data_abort(Dfsr::new_with_raw_value(0x211), Dfar(0x3000));
+        assert_eq!(fault.kind, FaultKind::AsyncExternal);
+        assert_eq!(fault.address, None);
+        assert_eq!(fault.bus_error, Some(BusError::Decode));
+    }
+
+    #[cfg(arm_architecture = "v8-r")]
+    #[test]
+    fn long_descriptor_prefetch_abort() {
+        let fault = Fault::from_prefetch_abort(Ifsr::new_with_raw_value(0x20C), Ifar(0x5000));
+        assert_eq!(
+            fault,
+            Fault {
+                kind: FaultKind::Permission,
+                address: Some(0x5000),
+                write: false,
+                bus_error: None,
+            }
+        );
+        let fault = Fault::from_prefetch_abort(Ifsr::new_with_raw_value(0x222), Ifar(0x5000));
+        assert_eq!(fault.kind, FaultKind::Debug);
+        assert_eq!(fault.address, None);
+    }
+}
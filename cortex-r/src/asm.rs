@@ -3,6 +3,7 @@
 /// Emit an DSB instruction
 #[inline]
 pub fn dsb() {
+    #[cfg(target_arch = "arm")]
     unsafe {
         core::arch::asm!("dsb");
     }
@@ -11,6 +12,7 @@ pub fn dsb() {
 /// Emit an ISB instruction
 #[inline]
 pub fn isb() {
+    #[cfg(target_arch = "arm")]
     unsafe {
         core::arch::asm!("isb");
     }
@@ -19,6 +21,7 @@ pub fn isb() {
 /// Emit an NOP instruction
 #[inline]
 pub fn nop() {
+    #[cfg(target_arch = "arm")]
     unsafe {
         core::arch::asm!("nop");
     }
@@ -27,6 +30,7 @@ pub fn nop() {
 /// Emit an WFI instruction
 #[inline]
 pub fn wfi() {
+    #[cfg(target_arch = "arm")]
     unsafe {
         core::arch::asm!("wfi");
     }
@@ -35,6 +39,7 @@ pub fn wfi() {
 /// Emit an WFE instruction
 #[inline]
 pub fn wfe() {
+    #[cfg(target_arch = "arm")]
     unsafe {
         core::arch::asm!("wfe");
     }
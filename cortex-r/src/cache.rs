@@ -0,0 +1,220 @@
+//! Cache and branch predictor maintenance
+//!
+//! These operations work on Armv7-R and Armv8-R. Each one waits (with a DSB,
+//! plus an ISB where instruction fetch is affected) until the maintenance is
+//! complete before returning.
+//!
+//! The address range operations act on every cache line which overlaps the
+//! given range, which may include data either side of it. The set/way
+//! operations act on every data or unified cache up to the Level of Coherence.
+//! They are intended for start-up and power management - use the address range
+//! operations for everything else.
+
+use crate::register::{clidr::CacheType, Ccsidr, Clidr, Csselr, Ctr};
+
+/// Invalidate the whole instruction cache (ICIALLU)
+///
+/// Use this after writing code into RAM, and before enabling the instruction
+/// cache.
+#[inline]
+pub fn invalidate_icache_all() {
+    // Safety: Invalidating the instruction cache has no effect on program
+    // state, only on performance.
+    #[cfg(target_arch = "arm")]
+    unsafe {
+        core::arch::asm!("mcr p15, 0, {}, c7, c5, 0", in(reg) 0, options(nostack, preserves_flags));
+    }
+    crate::asm::dsb();
+    crate::asm::isb();
+}
+
+/// Invalidate the whole branch predictor (BPIALL)
+///
+/// Use this after writing code into RAM, and before enabling branch
+/// prediction.
+#[inline]
+pub fn invalidate_branch_predictor() {
+    // Safety: Invalidating the branch predictor has no effect on program state,
+    // only on performance.
+    #[cfg(target_arch = "arm")]
+    unsafe {
+        core::arch::asm!("mcr p15, 0, {}, c7, c5, 6", in(reg) 0, options(nostack, preserves_flags));
+    }
+    crate::asm::dsb();
+    crate::asm::isb();
+}
+
+/// Clean the data cache lines covering the given range (DCCMVAC)
+///
+/// Any dirty data is written back to memory, e.g. so that a DMA engine can
+/// read it.
+#[inline]
+pub fn clean_dcache_range(start: usize, len: usize) {
+    for_each_line(start, len, DcacheOp::Clean);
+    crate::asm::dsb();
+}
+
+/// Invalidate the data cache lines covering the given range (DCIMVAC)
+///
+/// The next read from the range comes from memory, e.g. after a DMA engine has
+/// written to it.
+///
+/// # Safety
+///
+/// Any dirty data in the affected cache lines is thrown away, including data
+/// just outside the range if it shares a cache line. The caller must ensure
+/// nothing in those cache lines needs keeping.
+#[inline]
+pub unsafe fn invalidate_dcache_range(start: usize, len: usize) {
+    for_each_line(start, len, DcacheOp::Invalidate);
+    crate::asm::dsb();
+}
+
+/// Clean and invalidate the data cache lines covering the given range
+/// (DCCIMVAC)
+#[inline]
+pub fn clean_invalidate_dcache_range(start: usize, len: usize) {
+    for_each_line(start, len, DcacheOp::CleanInvalidate);
+    crate::asm::dsb();
+}
+
+/// Clean the whole data cache, by set/way (DCCSW)
+#[inline]
+pub fn clean_dcache_all() {
+    for_each_set_way(DcacheOp::Clean);
+}
+
+/// Invalidate the whole data cache, by set/way (DCISW)
+///
+/// You need this before enabling the data cache, because its contents are
+/// unknown at reset.
+///
+/// # Safety
+///
+/// Any dirty data in the data cache is thrown away. This is only safe if the
+/// data cache has never been enabled, or has just been cleaned.
+#[inline]
+pub unsafe fn invalidate_dcache_all() {
+    for_each_set_way(DcacheOp::Invalidate);
+}
+
+/// Clean and invalidate the whole data cache, by set/way (DCCISW)
+///
+/// Use this when disabling the data cache.
+#[inline]
+pub fn clean_invalidate_dcache_all() {
+    for_each_set_way(DcacheOp::CleanInvalidate);
+}
+
+/// The smallest data cache line size in the system, in bytes
+#[inline]
+pub fn dcache_line_size() -> usize {
+    Ctr::read().dcache_line_size()
+}
+
+/// The smallest instruction cache line size in the system, in bytes
+#[inline]
+pub fn icache_line_size() -> usize {
+    Ctr::read().icache_line_size()
+}
+
+/// The data cache maintenance operations
+#[derive(Clone, Copy)]
+enum DcacheOp {
+    Clean,
+    Invalidate,
+    CleanInvalidate,
+}
+
+impl DcacheOp {
+    /// Perform this operation on the line containing the given address
+    #[inline]
+    fn by_mva(self, _address: usize) {
+        // Safety: the caller has decided this operation is OK for this line
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            match self {
+                DcacheOp::Clean => {
+                    core::arch::asm!("mcr p15, 0, {}, c7, c10, 1", in(reg) _address, options(nostack, preserves_flags))
+                }
+                DcacheOp::Invalidate => {
+                    core::arch::asm!("mcr p15, 0, {}, c7, c6, 1", in(reg) _address, options(nostack, preserves_flags))
+                }
+                DcacheOp::CleanInvalidate => {
+                    core::arch::asm!("mcr p15, 0, {}, c7, c14, 1", in(reg) _address, options(nostack, preserves_flags))
+                }
+            }
+        }
+    }
+
+    /// Perform this operation on the given set/way/level
+    #[inline]
+    fn by_set_way(self, _set_way: u32) {
+        // Safety: the caller has decided this operation is OK for this line
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            match self {
+                DcacheOp::Clean => {
+                    core::arch::asm!("mcr p15, 0, {}, c7, c10, 2", in(reg) _set_way, options(nostack, preserves_flags))
+                }
+                DcacheOp::Invalidate => {
+                    core::arch::asm!("mcr p15, 0, {}, c7, c6, 2", in(reg) _set_way, options(nostack, preserves_flags))
+                }
+                DcacheOp::CleanInvalidate => {
+                    core::arch::asm!("mcr p15, 0, {}, c7, c14, 2", in(reg) _set_way, options(nostack, preserves_flags))
+                }
+            }
+        }
+    }
+}
+
+/// Perform an operation on every data cache line which overlaps the range
+fn for_each_line(start: usize, len: usize, op: DcacheOp) {
+    if len == 0 {
+        return;
+    }
+    let line_size = dcache_line_size();
+    let end = start.saturating_add(len);
+    let mut address = start & !(line_size - 1);
+    // Make sure any writes to the range have finished first
+    crate::asm::dsb();
+    while address < end {
+        op.by_mva(address);
+        address = match address.checked_add(line_size) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+}
+
+/// Perform an operation on every line of every data or unified cache, up to
+/// the Level of Coherence
+fn for_each_set_way(op: DcacheOp) {
+    let clidr = Clidr::read();
+    let loc = clidr.loc().value();
+    // Make sure any writes have finished first
+    crate::asm::dsb();
+    for level in 1..=loc {
+        match clidr.cache_type(level) {
+            Some(CacheType::Data | CacheType::Separate | CacheType::Unified) => {}
+            _ => continue,
+        }
+        let level_bits = u32::from(level - 1) << 1;
+        Csselr::write(Csselr::new_with_raw_value(level_bits));
+        crate::asm::isb();
+        let ccsidr = Ccsidr::read();
+        let ways = ccsidr.ways();
+        // The way number goes in the top bits
+        let way_shift = (ways - 1).leading_zeros() & 31;
+        let set_shift = ccsidr.line_size_log2();
+        for way in 0..ways {
+            for set in 0..ccsidr.sets() {
+                op.by_set_way((way << way_shift) | (set << set_shift) | level_bits);
+            }
+        }
+        crate::asm::dsb();
+    }
+    // Go back to selecting the L1 cache
+    Csselr::write(Csselr::new_with_raw_value(0));
+    crate::asm::isb();
+}
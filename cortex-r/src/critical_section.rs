@@ -1,25 +1,55 @@
 //! Code that implements the `critical-section` traits on Cortex-R.
 //!
 //! Only valid if you have a single core.
+//!
+//! With the `critical-section-single-core-fiq` feature, FIQ is masked as well
+//! as IRQ, so that data can be shared with an FIQ handler.
 
 use core::sync::atomic;
 
+/// Bit set in our restore state if IRQ was enabled on entry
+const IRQ_WAS_ACTIVE: u8 = 1 << 0;
+
+/// Bit set in our restore state if FIQ was enabled on entry
+#[cfg(feature = "critical-section-single-core-fiq")]
+const FIQ_WAS_ACTIVE: u8 = 1 << 1;
+
 struct SingleCoreCriticalSection;
 critical_section::set_impl!(SingleCoreCriticalSection);
 
 unsafe impl critical_section::Impl for SingleCoreCriticalSection {
     unsafe fn acquire() -> critical_section::RawRestoreState {
-        // the i bit means "masked"
-        let was_active = !crate::register::Cpsr::read().i();
+        let cpsr = crate::register::Cpsr::read();
+        // the i and f bits mean "masked"
+        let mut state = 0;
+        if !cpsr.i() {
+            state |= IRQ_WAS_ACTIVE;
+        }
+        #[cfg(feature = "critical-section-single-core-fiq")]
+        {
+            if !cpsr.f() {
+                state |= FIQ_WAS_ACTIVE;
+            }
+            crate::interrupt::disable_fiq();
+        }
         crate::interrupt::disable();
         atomic::compiler_fence(atomic::Ordering::SeqCst);
-        was_active
+        state
     }
 
-    unsafe fn release(was_active: critical_section::RawRestoreState) {
+    unsafe fn release(state: critical_section::RawRestoreState) {
+        atomic::compiler_fence(atomic::Ordering::SeqCst);
+        // Only re-enable FIQ if it was enabled before the critical section.
+        #[cfg(feature = "critical-section-single-core-fiq")]
+        if (state & FIQ_WAS_ACTIVE) != 0 {
+            // Safety: This is OK because we're releasing a lock that was
+            // entered with FIQ enabled
+            unsafe {
+                crate::interrupt::enable_fiq();
+            }
+        }
         // Only re-enable interrupts if they were enabled before the critical section.
-        if was_active {
-            atomic::compiler_fence(atomic::Ordering::SeqCst);
+        if (state & IRQ_WAS_ACTIVE) != 0 {
             // Safety: This is OK because we're releasing a lock that was
             // entered with interrupts enabled
             unsafe {
@@ -0,0 +1,94 @@
+//! Code that implements the `critical-section` traits on Cortex-R, using the
+//! GIC priority mask.
+//!
+//! Rather than masking IRQs, each critical section raises the priority mask to
+//! a ceiling, so interrupts with a higher priority than that can still be
+//! taken. Those handlers must not use `critical-section` protected data.
+//!
+//! On Armv8-R this is ICC_PMR in the GICv3 CPU interface, and the ceiling is
+//! set with [`set_critical_section_ceiling`]. On Armv7-R it is GICC_PMR in a
+//! memory-mapped GICv2 CPU Interface, which must first be given to us (along
+//! with the ceiling) with [`Gic::set_critical_section_ceiling`]. Until then,
+//! critical sections mask IRQs.
+//!
+//! Only valid if you have a single core.
+//!
+//! [`set_critical_section_ceiling`]: crate::gic::cpu::set_critical_section_ceiling
+//! [`Gic::set_critical_section_ceiling`]: crate::gic::v2::Gic::set_critical_section_ceiling
+
+use core::sync::atomic;
+
+#[cfg(arm_architecture = "v8-r")]
+use crate::gic::cpu;
+
+#[cfg(arm_architecture = "v7-r")]
+use crate::gic::v2;
+
+struct GicPriorityCriticalSection;
+critical_section::set_impl!(GicPriorityCriticalSection);
+
+#[cfg(arm_architecture = "v8-r")]
+unsafe impl critical_section::Impl for GicPriorityCriticalSection {
+    unsafe fn acquire() -> critical_section::RawRestoreState {
+        let previous = cpu::priority_mask();
+        let ceiling = cpu::critical_section_ceiling();
+        // Only ever raise the mask, so nested critical sections don't let
+        // anything new in. Writes to ICC_PMR are self-synchronising.
+        if ceiling < previous {
+            cpu::set_priority_mask(ceiling);
+        }
+        atomic::compiler_fence(atomic::Ordering::SeqCst);
+        previous
+    }
+
+    unsafe fn release(previous: critical_section::RawRestoreState) {
+        atomic::compiler_fence(atomic::Ordering::SeqCst);
+        // Put back whatever mask was set before the critical section
+        if cpu::priority_mask() != previous {
+            cpu::set_priority_mask(previous);
+        }
+    }
+}
+
+/// Our restore state if IRQ was enabled on entry, when there was no CPU
+/// Interface to use
+#[cfg(arm_architecture = "v7-r")]
+const IRQ_WAS_ACTIVE: u8 = 1 << 0;
+
+#[cfg(arm_architecture = "v7-r")]
+unsafe impl critical_section::Impl for GicPriorityCriticalSection {
+    unsafe fn acquire() -> critical_section::RawRestoreState {
+        // Only ever raise the mask, so nested critical sections don't let
+        // anything new in
+        let state = match v2::critical_section_acquire() {
+            Some(previous) => previous,
+            None => {
+                // No CPU Interface yet, so fall back to masking IRQs. The i bit
+                // means "masked".
+                let irq_was_active = !crate::register::Cpsr::read().i();
+                crate::interrupt::disable();
+                if irq_was_active {
+                    IRQ_WAS_ACTIVE
+                } else {
+                    0
+                }
+            }
+        };
+        atomic::compiler_fence(atomic::Ordering::SeqCst);
+        state
+    }
+
+    unsafe fn release(state: critical_section::RawRestoreState) {
+        atomic::compiler_fence(atomic::Ordering::SeqCst);
+        // Put back whatever mask was set before the critical section - or, if
+        // we masked IRQs instead, only re-enable them if they were enabled
+        // before the critical section
+        if !v2::critical_section_release(state) && (state & IRQ_WAS_ACTIVE) != 0 {
+            // Safety: This is OK because we're releasing a lock that was
+            // entered with interrupts enabled
+            unsafe {
+                crate::interrupt::enable();
+            }
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! Buffers for sharing memory with a DMA engine
+//!
+//! A DMA engine reads and writes memory directly, without going through the
+//! data cache. So before a device reads a buffer, any dirty lines must be
+//! cleaned out to memory, and after a device writes a buffer, any stale lines
+//! must be invalidated.
+//!
+//! A [`DmaBuffer`] is aligned and padded to a whole number of cache lines, so
+//! that the cache maintenance on it cannot affect any other variable. While it
+//! is lent to a device (as a [`DmaTransfer`]) the CPU cannot touch it, and the
+//! right cache maintenance is done on the way in and on the way out.
+//!
+//! Nothing here knows when the DMA engine has actually finished. Dropping a
+//! [`DmaTransfer`] gives the buffer back to the CPU straight away, even if the
+//! device is still using it.
+
+use core::sync::atomic::{compiler_fence, Ordering};
+
+/// A buffer of `N` values of type `T`, which can be lent to a DMA engine
+///
+/// It is aligned to 32 bytes on Armv7-R and 64 bytes on Armv8-R, which are the
+/// cache line sizes of the Cortex-R5 and Cortex-R52 respectively.
+#[cfg_attr(arm_architecture = "v8-r", repr(C, align(64)))]
+#[cfg_attr(not(arm_architecture = "v8-r"), repr(C, align(32)))]
+pub struct DmaBuffer<T: Copy, const N: usize> {
+    contents: [T; N],
+}
+
+impl<T: Copy, const N: usize> DmaBuffer<T, N> {
+    /// Create a new buffer with the given contents
+    pub const fn new(contents: [T; N]) -> DmaBuffer<T, N> {
+        DmaBuffer { contents }
+    }
+
+    /// Get the contents of the buffer
+    pub fn as_slice(&self) -> &[T] {
+        &self.contents
+    }
+
+    /// Get the contents of the buffer, for writing
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.contents
+    }
+
+    /// Lend the buffer to a device which is going to read from it
+    ///
+    /// Any data the CPU wrote to the buffer is cleaned out to memory.
+    pub fn start_transmit(&mut self) -> DmaTransfer<'_, T, N> {
+        compiler_fence(Ordering::SeqCst);
+        crate::cache::clean_dcache_range(self.address(), Self::len_bytes());
+        DmaTransfer {
+            buffer: self,
+            direction: Direction::ToDevice,
+        }
+    }
+
+    /// Lend the buffer to a device which is going to write to it
+    ///
+    /// The buffer is cleaned and invalidated, so that no dirty cache line can be
+    /// written back over the data from the device.
+    ///
+    /// # Safety
+    ///
+    /// The device must only write valid values of `T` into the buffer. Any bit
+    /// pattern is fine for integers, but not for types like `bool`, `char`,
+    /// enums or references.
+    pub unsafe fn start_receive(&mut self) -> DmaTransfer<'_, T, N> {
+        compiler_fence(Ordering::SeqCst);
+        crate::cache::clean_invalidate_dcache_range(self.address(), Self::len_bytes());
+        DmaTransfer {
+            buffer: self,
+            direction: Direction::FromDevice,
+        }
+    }
+
+    /// The start address of the buffer
+    fn address(&self) -> usize {
+        self.contents.as_ptr() as usize
+    }
+
+    /// The size of the buffer, in bytes, including any padding
+    const fn len_bytes() -> usize {
+        core::mem::size_of::<Self>()
+    }
+}
+
+/// Which way the data is going in a [`DmaTransfer`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    ToDevice,
+    FromDevice,
+}
+
+/// A [`DmaBuffer`] which has been lent to a device
+///
+/// Give the address and length to your DMA engine. When the DMA engine has
+/// finished, call [`DmaTransfer::finish`] (or drop this value) to give the
+/// buffer back to the CPU.
+///
+/// Dropping this early (including by panicking, or by returning early with
+/// `?`) hands the CPU a buffer which the device may still be reading or
+/// writing. Stop the DMA engine before that can happen.
+pub struct DmaTransfer<'a, T: Copy, const N: usize> {
+    buffer: &'a mut DmaBuffer<T, N>,
+    direction: Direction,
+}
+
+impl<T: Copy, const N: usize> DmaTransfer<'_, T, N> {
+    /// The start address of the buffer, to give to the DMA engine
+    pub fn address(&self) -> usize {
+        self.buffer.address()
+    }
+
+    /// The length of the buffer contents in bytes, to give to the DMA engine
+    pub fn len_bytes(&self) -> usize {
+        core::mem::size_of::<[T; N]>()
+    }
+
+    /// Give the buffer back to the CPU
+    ///
+    /// You must only call this once the DMA engine has finished with the
+    /// buffer. Otherwise the CPU gets the buffer back while the device is still
+    /// using it - and for a receive, the cache lines are invalidated before
+    /// the device has finished writing, so the CPU may see stale data.
+    pub fn finish(self) {
+        // the work is done in our Drop impl
+    }
+}
+
+impl<T: Copy, const N: usize> Drop for DmaTransfer<'_, T, N> {
+    fn drop(&mut self) {
+        if self.direction == Direction::FromDevice {
+            // Throw away anything the CPU speculatively loaded into the cache
+            // while the device was writing.
+            //
+            // Safety: the buffer is aligned and padded to whole cache lines, so
+            // no other data is affected, and the CPU hasn't written to it since
+            // it was cleaned.
+            unsafe {
+                crate::cache::invalidate_dcache_range(
+                    self.buffer.address(),
+                    DmaBuffer::<T, N>::len_bytes(),
+                );
+            }
+        }
+        compiler_fence(Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,228 @@
+//! The GICv3 CPU interface, as accessed through system registers
+//!
+//! This is how the Cortex-R52 talks to its GIC. These functions act on the
+//! interface for the current core, and handle Group 1 interrupts (which are
+//! signalled as IRQ) unless their name says otherwise. Group 0 interrupts are
+//! signalled as FIQ.
+
+#[cfg(feature = "critical-section-gic-priority")]
+use core::sync::atomic::AtomicU8;
+use core::sync::atomic::{compiler_fence, Ordering};
+
+use arbitrary_int::{u24, u3, u4};
+
+use super::IntId;
+use crate::register::{
+    IccBpr1, IccEoir0, IccEoir1, IccIar0, IccIar1, IccIgrpen0, IccIgrpen1, IccPmr, IccRpr, IccSgi1r,
+};
+
+/// The ceiling used by the `critical-section` implementation
+#[cfg(feature = "critical-section-gic-priority")]
+static CRITICAL_SECTION_CEILING: AtomicU8 = AtomicU8::new(0);
+
+/// The cores which should receive a Software Generated Interrupt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SgiTarget {
+    /// Every core except this one
+    AllOthers,
+    /// The listed cores, which share the given upper affinity levels
+    List {
+        /// Affinity level 3 of the target cores
+        affinity3: u8,
+        /// Affinity level 2 of the target cores
+        affinity2: u8,
+        /// Affinity level 1 of the target cores
+        affinity1: u8,
+        /// One bit for each affinity level 0 value (0 to 15) of the target
+        /// cores
+        target_list: u16,
+    },
+}
+
+/// Acknowledge the highest priority pending Group 1 interrupt
+///
+/// It becomes active, and stays active until you call [`end_interrupt`].
+/// Returns `None` if no interrupt is pending.
+#[inline]
+pub fn acknowledge() -> Option<IntId> {
+    let intid = IntId::from_raw(IccIar1::read().intid().value());
+    if intid == IntId::SPURIOUS {
+        None
+    } else {
+        Some(intid)
+    }
+}
+
+/// Mark a Group 1 interrupt as no longer active
+#[inline]
+pub fn end_interrupt(intid: IntId) {
+    IccEoir1::write(IccEoir1::new_with_raw_value(0).with_intid(u24::new(intid.to_raw())));
+}
+
+/// Acknowledge the highest priority pending Group 0 interrupt
+///
+/// Call this from your FIQ handler. The interrupt becomes active, and stays
+/// active until you call [`end_interrupt_group0`]. Returns `None` if no
+/// interrupt is pending.
+#[inline]
+pub fn acknowledge_group0() -> Option<IntId> {
+    let intid = IntId::from_raw(IccIar0::read().intid().value());
+    if intid == IntId::SPURIOUS {
+        None
+    } else {
+        Some(intid)
+    }
+}
+
+/// Mark a Group 0 interrupt as no longer active
+#[inline]
+pub fn end_interrupt_group0(intid: IntId) {
+    IccEoir0::write(IccEoir0::new_with_raw_value(0).with_intid(u24::new(intid.to_raw())));
+}
+
+/// Set the priority mask
+///
+/// Only interrupts with a higher priority (i.e. a lower value) than this are
+/// signalled to the core.
+#[inline]
+pub fn set_priority_mask(priority: u8) {
+    IccPmr::write(IccPmr::new_with_raw_value(0).with_priority(priority));
+}
+
+/// Get the priority mask
+#[inline]
+pub fn priority_mask() -> u8 {
+    IccPmr::read().priority()
+}
+
+/// Run a closure with the priority mask raised to a ceiling
+///
+/// Interrupts with a priority value of `ceiling` or more are held off while
+/// `f` runs, but higher priority interrupts can still preempt it - like
+/// BASEPRI on Cortex-M. If the mask is already at or below `ceiling`, it is
+/// left alone. The previous mask is restored afterwards.
+///
+/// This only excludes interrupt handlers at or below the ceiling. It is not
+/// a [`CriticalSection`](crate::interrupt::CriticalSection), and is only
+/// sound on a single core.
+#[inline]
+pub fn with_priority_ceiling<F, T>(ceiling: u8, f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    let previous = priority_mask();
+    // Writes to ICC_PMR are self-synchronising, so no lower priority interrupt
+    // is taken after this without needing an ISB
+    if ceiling < previous {
+        set_priority_mask(ceiling);
+    }
+    compiler_fence(Ordering::SeqCst);
+    let result = f();
+    compiler_fence(Ordering::SeqCst);
+    if ceiling < previous {
+        set_priority_mask(previous);
+    }
+    result
+}
+
+/// Set the ceiling used by the `critical-section` implementation
+///
+/// Each critical section raises the priority mask to this, so only
+/// interrupts with a lower value (i.e. a higher priority) can preempt it. The
+/// default is 0, which holds off every Group 1 interrupt. Those higher
+/// priority handlers must not use `critical-section` protected data.
+#[cfg(feature = "critical-section-gic-priority")]
+#[inline]
+pub fn set_critical_section_ceiling(ceiling: u8) {
+    CRITICAL_SECTION_CEILING.store(ceiling, Ordering::Relaxed);
+}
+
+/// Get the ceiling used by the `critical-section` implementation
+#[cfg(feature = "critical-section-gic-priority")]
+#[inline]
+pub fn critical_section_ceiling() -> u8 {
+    CRITICAL_SECTION_CEILING.load(Ordering::Relaxed)
+}
+
+/// Get the priority of the highest priority active interrupt
+///
+/// Returns 0xFF if no interrupt is active.
+#[inline]
+pub fn running_priority() -> u8 {
+    IccRpr::read().priority()
+}
+
+/// Set the binary point for Group 1 interrupts
+///
+/// Priority bits above the binary point form the group priority, which decides
+/// whether one interrupt can preempt another. Values above 7 are limited to 7.
+#[inline]
+pub fn set_binary_point(binary_point: u8) {
+    IccBpr1::write(IccBpr1::new_with_raw_value(0).with_binary_point(u3::new(binary_point.min(7))));
+}
+
+/// Get the binary point for Group 1 interrupts
+#[inline]
+pub fn binary_point() -> u8 {
+    IccBpr1::read().binary_point().value()
+}
+
+/// Enable or disable signalling of Group 1 interrupts to this core
+#[inline]
+pub fn enable_group1(enable: bool) {
+    IccIgrpen1::write(IccIgrpen1::new_with_raw_value(0).with_enable(enable));
+    crate::asm::isb();
+}
+
+/// Enable or disable signalling of Group 0 interrupts (as FIQ) to this core
+#[inline]
+pub fn enable_group0(enable: bool) {
+    IccIgrpen0::write(IccIgrpen0::new_with_raw_value(0).with_enable(enable));
+    crate::asm::isb();
+}
+
+/// Make [`DEFERRED_SGI`](super::DEFERRED_SGI) pending on this core
+///
+/// Its handler runs once every higher priority interrupt has been handled,
+/// like PendSV on Cortex-M. Pending it again before the handler has run has no
+/// further effect. Call `v3::Gic::enable_deferred` first.
+#[inline]
+pub fn pend_deferred() {
+    let this = super::v3::Affinity::current();
+    send_sgi(
+        super::DEFERRED_SGI,
+        SgiTarget::List {
+            affinity3: this.aff3,
+            affinity2: this.aff2,
+            affinity1: this.aff1,
+            target_list: 1 << this.aff0,
+        },
+    );
+}
+
+/// Generate a Group 1 Software Generated Interrupt
+///
+/// Panics if `intid` is not an SGI.
+#[inline]
+pub fn send_sgi(intid: IntId, target: SgiTarget) {
+    assert!(intid.is_sgi(), "not an SGI");
+    let sgi1r = IccSgi1r::new_with_raw_value(0).with_intid(u4::new(intid.to_raw() as u8));
+    let sgi1r = match target {
+        SgiTarget::AllOthers => sgi1r.with_irm(true),
+        SgiTarget::List {
+            affinity3,
+            affinity2,
+            affinity1,
+            target_list,
+        } => sgi1r
+            .with_aff3(affinity3)
+            .with_aff2(affinity2)
+            .with_aff1(affinity1)
+            .with_target_list(target_list),
+    };
+    // Make sure any data the handler will look at is visible first
+    crate::asm::dsb();
+    IccSgi1r::write(sgi1r);
+    crate::asm::isb();
+}
@@ -0,0 +1,313 @@
+//! Support for the Arm Generic Interrupt Controller (GIC)
+//!
+//! On Armv8-R processors like the Cortex-R52, the GIC CPU interface is
+//! accessed through system registers, and is supported by the `cpu` module.
+//! The memory-mapped GICv3 Distributor and Redistributors are supported by the
+//! `v3` module.
+//!
+//! The GICv2 (e.g. the GIC-390 found next to many Cortex-R5s) has a
+//! memory-mapped CPU interface, and is supported by the `v2` module.
+
+#[cfg(arm_architecture = "v8-r")]
+pub mod cpu;
+
+pub mod v2;
+
+#[cfg(arm_architecture = "v8-r")]
+pub mod v3;
+
+/// A GIC Interrupt ID
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct IntId(u32);
+
+impl IntId {
+    /// The number of Software Generated Interrupts
+    pub const SGI_COUNT: u32 = 16;
+
+    /// The number of Private Peripheral Interrupts
+    pub const PPI_COUNT: u32 = 16;
+
+    /// The ID of the first Shared Peripheral Interrupt
+    pub const SPI_START: u32 = Self::SGI_COUNT + Self::PPI_COUNT;
+
+    /// The ID of the first special interrupt ID
+    pub const SPECIAL_START: u32 = 1020;
+
+    /// The ID returned on acknowledge when there is no pending interrupt
+    pub const SPURIOUS: IntId = IntId(1023);
+
+    /// Create an ID for the given Software Generated Interrupt
+    ///
+    /// Panics if `sgi` is not less than 16.
+    pub const fn sgi(sgi: u32) -> IntId {
+        assert!(sgi < Self::SGI_COUNT, "invalid SGI number");
+        IntId(sgi)
+    }
+
+    /// Create an ID for the given Private Peripheral Interrupt
+    ///
+    /// Panics if `ppi` is not less than 16.
+    pub const fn ppi(ppi: u32) -> IntId {
+        assert!(ppi < Self::PPI_COUNT, "invalid PPI number");
+        IntId(Self::SGI_COUNT + ppi)
+    }
+
+    /// Create an ID for the given Shared Peripheral Interrupt
+    ///
+    /// Panics if the ID would be a special interrupt ID.
+    pub const fn spi(spi: u32) -> IntId {
+        assert!(
+            spi < Self::SPECIAL_START - Self::SPI_START,
+            "invalid SPI number"
+        );
+        IntId(Self::SPI_START + spi)
+    }
+
+    /// Create an ID from a raw interrupt ID value
+    pub const fn from_raw(id: u32) -> IntId {
+        IntId(id)
+    }
+
+    /// Get the raw interrupt ID value
+    pub const fn to_raw(self) -> u32 {
+        self.0
+    }
+
+    /// Is this a Software Generated Interrupt?
+    pub const fn is_sgi(self) -> bool {
+        self.0 < Self::SGI_COUNT
+    }
+
+    /// Is this a Private Peripheral Interrupt?
+    pub const fn is_ppi(self) -> bool {
+        self.0 >= Self::SGI_COUNT && self.0 < Self::SPI_START
+    }
+
+    /// Is this a Shared Peripheral Interrupt?
+    pub const fn is_spi(self) -> bool {
+        self.0 >= Self::SPI_START && self.0 < Self::SPECIAL_START
+    }
+
+    /// Is this one of the special interrupt IDs (1020 to 1023)?
+    pub const fn is_special(self) -> bool {
+        self.0 >= Self::SPECIAL_START && self.0 < 1024
+    }
+}
+
+impl From<IntId> for u32 {
+    fn from(intid: IntId) -> u32 {
+        intid.to_raw()
+    }
+}
+
+impl core::fmt::Debug for IntId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.is_sgi() {
+            write!(f, "SGI {}", self.0)
+        } else if self.is_ppi() {
+            write!(f, "PPI {}", self.0 - Self::SGI_COUNT)
+        } else if self.is_spi() {
+            write!(f, "SPI {}", self.0 - Self::SPI_START)
+        } else {
+            write!(f, "IntId {}", self.0)
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for IntId {
+    fn format(&self, f: defmt::Formatter) {
+        if self.is_sgi() {
+            defmt::write!(f, "SGI {=u32}", self.0)
+        } else if self.is_ppi() {
+            defmt::write!(f, "PPI {=u32}", self.0 - Self::SGI_COUNT)
+        } else if self.is_spi() {
+            defmt::write!(f, "SPI {=u32}", self.0 - Self::SPI_START)
+        } else {
+            defmt::write!(f, "IntId {=u32}", self.0)
+        }
+    }
+}
+
+/// The SGI used for deferred work
+///
+/// Cortex-R processors have no PendSV, so we use an SGI to this core at the
+/// lowest priority instead. See `v2::Gic::pend_deferred` (or
+/// `cpu::pend_deferred` on Armv8-R). Don't use it for anything else.
+pub const DEFERRED_SGI: IntId = IntId::sgi(15);
+
+/// The lowest priority which a priority mask of 0xFF still lets through
+///
+/// `all_ones` is what an interrupt's priority reads back as after writing 0xFF
+/// to it, which tells us which priority bits the GIC implements.
+fn lowest_unmasked_priority(all_ones: u8) -> u8 {
+    // The lowest implemented bit is the step between priority levels
+    all_ones - (all_ones & all_ones.wrapping_neg())
+}
+
+/// How an interrupt is triggered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Trigger {
+    /// The interrupt is pending while the line is asserted
+    Level,
+    /// The interrupt becomes pending on a rising edge
+    Edge,
+}
+
+/// The interrupt group an interrupt belongs to
+///
+/// Cortex-R processors only have one Security state, so Group 0 interrupts are
+/// signalled as FIQ and Group 1 interrupts are signalled as IRQ.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Group {
+    /// Group 0, signalled as FIQ
+    Group0,
+    /// Group 1, signalled as IRQ
+    Group1,
+}
+
+/// A block of memory-mapped GIC registers
+#[derive(Clone, Copy)]
+struct Mmio(*mut u32);
+
+impl Mmio {
+    /// Read the 32-bit register at the given byte offset
+    unsafe fn read(self, offset: usize) -> u32 {
+        // Safety: the caller has promised this register exists
+        unsafe { self.0.byte_add(offset).read_volatile() }
+    }
+
+    /// Write the 32-bit register at the given byte offset
+    unsafe fn write(self, offset: usize, value: u32) {
+        // Safety: the caller has promised this register exists
+        unsafe { self.0.byte_add(offset).write_volatile(value) }
+    }
+
+    /// Read, modify and write the 32-bit register at the given byte offset
+    unsafe fn modify<F>(self, offset: usize, f: F)
+    where
+        F: FnOnce(u32) -> u32,
+    {
+        // Safety: the caller has promised this register exists
+        unsafe { self.write(offset, f(self.read(offset))) }
+    }
+
+    /// Read the 8-bit register at the given byte offset
+    unsafe fn read_u8(self, offset: usize) -> u8 {
+        // Safety: the caller has promised this register exists
+        unsafe { self.0.cast::<u8>().add(offset).read_volatile() }
+    }
+
+    /// Write the 8-bit register at the given byte offset
+    unsafe fn write_u8(self, offset: usize, value: u8) {
+        // Safety: the caller has promised this register exists
+        unsafe { self.0.cast::<u8>().add(offset).write_volatile(value) }
+    }
+
+    /// Write a one to the bit for the given interrupt, in a bit-per-interrupt
+    /// array of registers starting at the given byte offset
+    unsafe fn write_bit(self, offset: usize, intid: IntId) {
+        let id = intid.to_raw() as usize;
+        // Safety: the caller has promised this register exists
+        unsafe { self.write(offset + (id / 32) * 4, 1 << (id % 32)) }
+    }
+
+    /// Set or clear the bit for the given interrupt, in a bit-per-interrupt
+    /// array of registers starting at the given byte offset
+    unsafe fn modify_bit(self, offset: usize, intid: IntId, value: bool) {
+        let id = intid.to_raw() as usize;
+        let bit = 1 << (id % 32);
+        // Safety: the caller has promised this register exists
+        unsafe {
+            self.modify(offset + (id / 32) * 4, |w| {
+                if value {
+                    w | bit
+                } else {
+                    w & !bit
+                }
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::format;
+
+    #[test]
+    fn intid_constructors() {
+        assert_eq!(IntId::sgi(0).to_raw(), 0);
+        assert_eq!(IntId::sgi(15).to_raw(), 15);
+        assert_eq!(IntId::ppi(0).to_raw(), 16);
+        assert_eq!(IntId::ppi(15).to_raw(), 31);
+        assert_eq!(IntId::spi(0).to_raw(), 32);
+        assert_eq!(IntId::spi(987).to_raw(), 1019);
+        assert_eq!(IntId::from_raw(1023), IntId::SPURIOUS);
+        assert_eq!(u32::from(IntId::spi(5)), 37);
+    }
+
+    #[test]
+    #[should_panic]
+    fn intid_sgi_out_of_range() {
+        IntId::sgi(16);
+    }
+
+    #[test]
+    #[should_panic]
+    fn intid_ppi_out_of_range() {
+        IntId::ppi(16);
+    }
+
+    #[test]
+    #[should_panic]
+    fn intid_spi_out_of_range() {
+        IntId::spi(988);
+    }
+
+    #[test]
+    fn intid_classification() {
+        let check = |id: IntId, sgi, ppi, spi, special| {
+            assert_eq!(id.is_sgi(), sgi, "{:?}", id);
+            assert_eq!(id.is_ppi(), ppi, "{:?}", id);
+            assert_eq!(id.is_spi(), spi, "{:?}", id);
+            assert_eq!(id.is_special(), special, "{:?}", id);
+        };
+        check(IntId::from_raw(0), true, false, false, false);
+        check(IntId::from_raw(15), true, false, false, false);
+        check(IntId::from_raw(16), false, true, false, false);
+        check(IntId::from_raw(31), false, true, false, false);
+        check(IntId::from_raw(32), false, false, true, false);
+        check(IntId::from_raw(1019), false, false, true, false);
+        check(IntId::from_raw(1020), false, false, false, true);
+        check(IntId::SPURIOUS, false, false, false, true);
+        check(IntId::from_raw(1024), false, false, false, false);
+    }
+
+    #[test]
+    fn intid_debug() {
+        assert_eq!(format!("{:?}", IntId::sgi(3)), "SGI 3");
+        assert_eq!(format!("{:?}", IntId::ppi(14)), "PPI 14");
+        assert_eq!(format!("{:?}", IntId::spi(100)), "SPI 100");
+        assert_eq!(format!("{:?}", IntId::SPURIOUS), "IntId 1023");
+    }
+
+    #[test]
+    fn lowest_unmasked_priority_steps() {
+        // All eight bits implemented
+        assert_eq!(lowest_unmasked_priority(0xFF), 0xFE);
+        // Five bits implemented, so 32 levels in steps of 8
+        assert_eq!(lowest_unmasked_priority(0xF8), 0xF0);
+        // Four bits implemented, so 16 levels in steps of 16
+        assert_eq!(lowest_unmasked_priority(0xF0), 0xE0);
+        // Only one bit implemented
+        assert_eq!(lowest_unmasked_priority(0x80), 0x00);
+        // Nothing implemented
+        assert_eq!(lowest_unmasked_priority(0x00), 0x00);
+    }
+}
@@ -0,0 +1,466 @@
+//! A driver for a GICv2 (e.g. a GIC-390), with a memory-mapped CPU interface
+//!
+//! This is the interrupt controller used alongside the Cortex-R5 on many SoCs,
+//! such as the Zynq UltraScale+ RPU. Your SoC's reference manual will give the
+//! addresses of the Distributor (GICD) and CPU Interface (GICC).
+
+#[cfg(all(arm_architecture = "v7-r", feature = "critical-section-gic-priority"))]
+use core::sync::atomic::AtomicPtr;
+use core::sync::atomic::{compiler_fence, AtomicU8, Ordering};
+
+use super::{IntId, Mmio};
+use crate::interrupt::InterruptController;
+
+#[doc(inline)]
+pub use super::{Group, Trigger};
+
+// Distributor registers
+const GICD_CTLR: usize = 0x0000;
+const GICD_TYPER: usize = 0x0004;
+const GICD_IGROUPR: usize = 0x0080;
+const GICD_ISENABLER: usize = 0x0100;
+const GICD_ICENABLER: usize = 0x0180;
+const GICD_ISPENDR: usize = 0x0200;
+const GICD_ICPENDR: usize = 0x0280;
+const GICD_IPRIORITYR: usize = 0x0400;
+const GICD_ITARGETSR: usize = 0x0800;
+const GICD_ICFGR: usize = 0x0C00;
+const GICD_SGIR: usize = 0x0F00;
+
+const GICD_CTLR_ENABLE_GRP0: u32 = 1 << 0;
+const GICD_CTLR_ENABLE_GRP1: u32 = 1 << 1;
+
+// CPU Interface registers
+const GICC_CTLR: usize = 0x0000;
+const GICC_PMR: usize = 0x0004;
+const GICC_BPR: usize = 0x0008;
+const GICC_IAR: usize = 0x000C;
+const GICC_EOIR: usize = 0x0010;
+const GICC_RPR: usize = 0x0014;
+
+const GICC_CTLR_ENABLE_GRP0: u32 = 1 << 0;
+const GICC_CTLR_ENABLE_GRP1: u32 = 1 << 1;
+const GICC_CTLR_ACK_CTL: u32 = 1 << 2;
+const GICC_CTLR_FIQ_EN: u32 = 1 << 3;
+
+/// The CPU Interface used by the `critical-section` implementation (or null)
+#[cfg(all(arm_architecture = "v7-r", feature = "critical-section-gic-priority"))]
+static CRITICAL_SECTION_GICC: AtomicPtr<u32> = AtomicPtr::new(core::ptr::null_mut());
+
+/// The ceiling used by the `critical-section` implementation
+#[cfg(all(arm_architecture = "v7-r", feature = "critical-section-gic-priority"))]
+static CRITICAL_SECTION_CEILING: AtomicU8 = AtomicU8::new(0);
+
+/// The cores which should receive a Software Generated Interrupt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SgiTarget {
+    /// The cores in the list, with one bit for each of CPU 0 to CPU 7
+    List(u8),
+    /// Every core except this one
+    AllOthers,
+    /// Only this core
+    This,
+}
+
+/// A driver for a GICv2 Distributor and CPU Interface
+pub struct Gic {
+    gicd: Mmio,
+    gicc: Mmio,
+    /// Which CPU sent each SGI we have acknowledged, as we need it for the EOI
+    sgi_source: [AtomicU8; 16],
+}
+
+// Safety: the driver only holds the addresses of the GIC. Anything which
+// changes the configuration of the GIC takes `&mut self`, and the rest is
+// single register accesses.
+unsafe impl Send for Gic {}
+unsafe impl Sync for Gic {}
+
+impl Gic {
+    /// Create a driver from the Distributor and CPU Interface addresses
+    ///
+    /// # Safety
+    ///
+    /// The addresses must point at a GICv2 Distributor and CPU Interface.
+    /// There must only be one driver for them at any one time.
+    pub unsafe fn new(gicd: *mut u32, gicc: *mut u32) -> Gic {
+        Gic {
+            gicd: Mmio(gicd),
+            gicc: Mmio(gicc),
+            sgi_source: [const { AtomicU8::new(0) }; 16],
+        }
+    }
+
+    /// The number of interrupt IDs the Distributor supports (including SGIs and
+    /// PPIs)
+    pub fn num_interrupts(&self) -> u32 {
+        // Safety: the address was given to us in `new`
+        let typer = unsafe { self.gicd.read(GICD_TYPER) };
+        (((typer & 0x1F) + 1) * 32).min(IntId::SPECIAL_START)
+    }
+
+    /// Initialise the Distributor and CPU Interface
+    ///
+    /// Disables every interrupt, puts it in Group 1, gives it the lowest
+    /// priority (0xFF) and routes every SPI to this core. Then enables both
+    /// groups in the Distributor and the CPU Interface, with Group 0 signalled
+    /// as FIQ, and with a priority mask that lets through every interrupt with
+    /// a higher priority than that - so remember to set a priority for each
+    /// interrupt you enable.
+    pub fn init(&mut self) {
+        let num_interrupts = self.num_interrupts() as usize;
+        // Safety: the addresses were given to us in `new`
+        unsafe {
+            self.gicd.write(GICD_CTLR, 0);
+            for id in (0..num_interrupts).step_by(32) {
+                self.gicd.write(GICD_ICENABLER + id / 8, 0xFFFF_FFFF);
+                self.gicd.write(GICD_ICPENDR + id / 8, 0xFFFF_FFFF);
+                self.gicd.write(GICD_IGROUPR + id / 8, 0xFFFF_FFFF);
+            }
+            for id in (0..num_interrupts).step_by(4) {
+                self.gicd.write(GICD_IPRIORITYR + id, 0xFFFF_FFFF);
+            }
+            // The banked ITARGETSR registers for SGIs read as this core's bit
+            let this_core = self.gicd.read(GICD_ITARGETSR) & 0xFF;
+            for id in (IntId::SPI_START as usize..num_interrupts).step_by(4) {
+                self.gicd
+                    .write(GICD_ITARGETSR + id, this_core * 0x0101_0101);
+            }
+            self.gicd
+                .write(GICD_CTLR, GICD_CTLR_ENABLE_GRP0 | GICD_CTLR_ENABLE_GRP1);
+
+            self.gicc.write(GICC_PMR, 0xFF);
+            self.gicc.write(GICC_BPR, 0);
+            // With AckCtl set, GICC_IAR and GICC_EOIR handle both groups
+            self.gicc.write(
+                GICC_CTLR,
+                GICC_CTLR_ENABLE_GRP0
+                    | GICC_CTLR_ENABLE_GRP1
+                    | GICC_CTLR_ACK_CTL
+                    | GICC_CTLR_FIQ_EN,
+            );
+        }
+    }
+
+    /// Enable or disable an interrupt
+    pub fn enable_interrupt(&mut self, intid: IntId, enable: bool) {
+        let offset = if enable {
+            GICD_ISENABLER
+        } else {
+            GICD_ICENABLER
+        };
+        // Safety: the address was given to us in `new`
+        unsafe {
+            self.gicd.write_bit(offset, intid);
+        }
+    }
+
+    /// Set or clear the pending state of an interrupt
+    pub fn set_pending(&mut self, intid: IntId, pending: bool) {
+        let offset = if pending { GICD_ISPENDR } else { GICD_ICPENDR };
+        // Safety: the address was given to us in `new`
+        unsafe {
+            self.gicd.write_bit(offset, intid);
+        }
+    }
+
+    /// Set the priority of an interrupt
+    ///
+    /// Lower values are higher priority. The GIC may ignore some of the low
+    /// bits.
+    pub fn set_priority(&mut self, intid: IntId, priority: u8) {
+        // Safety: the address was given to us in `new`
+        unsafe {
+            self.gicd
+                .write_u8(GICD_IPRIORITYR + intid.to_raw() as usize, priority);
+        }
+    }
+
+    /// Set which group an interrupt belongs to
+    ///
+    /// Put an interrupt in Group 0 to have it signalled as FIQ. Give it a
+    /// higher priority than every Group 1 interrupt, and acknowledge it from
+    /// your FIQ handler with [`Gic::acknowledge`].
+    pub fn set_group(&mut self, intid: IntId, group: Group) {
+        // Safety: the address was given to us in `new`
+        unsafe {
+            self.gicd
+                .modify_bit(GICD_IGROUPR, intid, group == Group::Group1);
+        }
+    }
+
+    /// Get the priority of an interrupt
+    pub fn priority(&self, intid: IntId) -> u8 {
+        // Safety: the address was given to us in `new`
+        unsafe { self.gicd.read_u8(GICD_IPRIORITYR + intid.to_raw() as usize) }
+    }
+
+    /// Set whether an interrupt is level or edge triggered
+    ///
+    /// SGIs are always edge triggered, and this does nothing for them.
+    pub fn set_trigger(&mut self, intid: IntId, trigger: Trigger) {
+        if intid.is_sgi() {
+            return;
+        }
+        let id = intid.to_raw() as usize;
+        let offset = GICD_ICFGR + (id / 16) * 4;
+        let bit = 1 << ((id % 16) * 2 + 1);
+        // Safety: the address was given to us in `new`
+        unsafe {
+            self.gicd.modify(offset, |w| match trigger {
+                Trigger::Level => w & !bit,
+                Trigger::Edge => w | bit,
+            });
+        }
+    }
+
+    /// Set which cores an SPI is delivered to, with one bit for each of CPU 0
+    /// to CPU 7
+    ///
+    /// Panics if `intid` is not an SPI.
+    pub fn set_target(&mut self, intid: IntId, cpu_mask: u8) {
+        assert!(intid.is_spi(), "only SPIs can be targeted");
+        // Safety: the address was given to us in `new`
+        unsafe {
+            self.gicd
+                .write_u8(GICD_ITARGETSR + intid.to_raw() as usize, cpu_mask);
+        }
+    }
+
+    /// Acknowledge the highest priority pending interrupt
+    ///
+    /// It becomes active, and stays active until you call
+    /// [`Gic::end_interrupt`]. Returns `None` if no interrupt is pending. This
+    /// works for both groups, so use it from your FIQ handler too.
+    pub fn acknowledge(&self) -> Option<IntId> {
+        // Safety: the address was given to us in `new`
+        let iar = unsafe { self.gicc.read(GICC_IAR) };
+        let intid = IntId::from_raw(iar & 0x3FF);
+        if intid == IntId::SPURIOUS {
+            return None;
+        }
+        if intid.is_sgi() {
+            let source = ((iar >> 10) & 0x7) as u8;
+            self.sgi_source[intid.to_raw() as usize].store(source, Ordering::Relaxed);
+        }
+        Some(intid)
+    }
+
+    /// Mark an interrupt as no longer active
+    pub fn end_interrupt(&self, intid: IntId) {
+        let mut eoir = intid.to_raw();
+        if intid.is_sgi() {
+            let source = self.sgi_source[intid.to_raw() as usize].load(Ordering::Relaxed);
+            eoir |= u32::from(source) << 10;
+        }
+        // Safety: the address was given to us in `new`
+        unsafe {
+            self.gicc.write(GICC_EOIR, eoir);
+        }
+    }
+
+    /// Set the priority mask
+    ///
+    /// Only interrupts with a higher priority (i.e. a lower value) than this are
+    /// signalled to the core.
+    pub fn set_priority_mask(&self, priority: u8) {
+        // Safety: the address was given to us in `new`
+        unsafe {
+            self.gicc.write(GICC_PMR, u32::from(priority));
+        }
+    }
+
+    /// Get the priority mask
+    pub fn priority_mask(&self) -> u8 {
+        // Safety: the address was given to us in `new`
+        unsafe { self.gicc.read(GICC_PMR) as u8 }
+    }
+
+    /// Run a closure with the priority mask raised to a ceiling
+    ///
+    /// Interrupts with a priority value of `ceiling` or more are held off while
+    /// `f` runs, but higher priority interrupts can still preempt it - like
+    /// BASEPRI on Cortex-M. If the mask is already at or below `ceiling`, it is
+    /// left alone. The previous mask is restored afterwards.
+    ///
+    /// This only excludes interrupt handlers at or below the ceiling. It is not
+    /// a [`CriticalSection`](crate::interrupt::CriticalSection), and is only
+    /// sound on a single core.
+    pub fn with_priority_ceiling<F, T>(&self, ceiling: u8, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        // Safety: the address was given to us in `new`
+        let previous = unsafe { raise_priority_mask(self.gicc, ceiling) };
+        compiler_fence(Ordering::SeqCst);
+        let result = f();
+        compiler_fence(Ordering::SeqCst);
+        if ceiling < previous {
+            self.set_priority_mask(previous);
+        }
+        result
+    }
+
+    /// Use this CPU Interface for the `critical-section` implementation, and
+    /// set the priority mask it raises to
+    ///
+    /// Each critical section then raises GICC_PMR to `ceiling`, so interrupts
+    /// with a higher priority (a lower value) can still be taken. Those
+    /// handlers must not use `critical-section` protected data. Until this is
+    /// first called, critical sections mask IRQs instead - so call it before
+    /// enabling interrupts, and not from inside a critical section.
+    #[cfg(all(arm_architecture = "v7-r", feature = "critical-section-gic-priority"))]
+    pub fn set_critical_section_ceiling(&self, ceiling: u8) {
+        CRITICAL_SECTION_CEILING.store(ceiling, Ordering::Relaxed);
+        CRITICAL_SECTION_GICC.store(self.gicc.0, Ordering::Release);
+    }
+
+    /// Get the priority of the highest priority active interrupt
+    ///
+    /// Returns 0xFF if no interrupt is active.
+    pub fn running_priority(&self) -> u8 {
+        // Safety: the address was given to us in `new`
+        unsafe { self.gicc.read(GICC_RPR) as u8 }
+    }
+
+    /// Set the binary point
+    ///
+    /// Priority bits above the binary point form the group priority, which
+    /// decides whether one interrupt can preempt another. Values above 7 are
+    /// limited to 7.
+    pub fn set_binary_point(&self, binary_point: u8) {
+        // Safety: the address was given to us in `new`
+        unsafe {
+            self.gicc.write(GICC_BPR, u32::from(binary_point.min(7)));
+        }
+    }
+
+    /// Set up [`DEFERRED_SGI`](super::DEFERRED_SGI) for deferred work
+    ///
+    /// Gives it the lowest priority that the priority mask from [`Gic::init`]
+    /// lets through, and enables it. Give it a handler (e.g. with
+    /// `cortex_r_rt::register_deferred`) before calling
+    /// [`Gic::pend_deferred`].
+    pub fn enable_deferred(&mut self) {
+        let intid = super::DEFERRED_SGI;
+        self.set_priority(intid, 0xFF);
+        let priority = super::lowest_unmasked_priority(self.priority(intid));
+        self.set_priority(intid, priority);
+        self.enable_interrupt(intid, true);
+    }
+
+    /// Make [`DEFERRED_SGI`](super::DEFERRED_SGI) pending on this core
+    ///
+    /// Its handler runs once every higher priority interrupt has been handled,
+    /// like PendSV on Cortex-M. Pending it again before the handler has run
+    /// has no further effect. Call [`Gic::enable_deferred`] first.
+    pub fn pend_deferred(&self) {
+        self.send_sgi(super::DEFERRED_SGI, SgiTarget::This);
+    }
+
+    /// Generate a Software Generated Interrupt
+    ///
+    /// Panics if `intid` is not an SGI.
+    pub fn send_sgi(&self, intid: IntId, target: SgiTarget) {
+        assert!(intid.is_sgi(), "not an SGI");
+        let (filter, cpu_list) = match target {
+            SgiTarget::List(cpu_list) => (0b00, cpu_list),
+            SgiTarget::AllOthers => (0b01, 0),
+            SgiTarget::This => (0b10, 0),
+        };
+        // Make sure any data the handler will look at is visible first
+        crate::asm::dsb();
+        // Safety: the address was given to us in `new`
+        unsafe {
+            self.gicd.write(
+                GICD_SGIR,
+                (filter << 24) | (u32::from(cpu_list) << 16) | intid.to_raw(),
+            );
+        }
+    }
+}
+
+/// Raise the priority mask to `ceiling`, unless it is already at or below it,
+/// and return the previous mask
+///
+/// # Safety
+///
+/// `gicc` must point at a GICv2 CPU Interface.
+unsafe fn raise_priority_mask(gicc: Mmio, ceiling: u8) -> u8 {
+    // Safety: the caller has promised this is a CPU Interface
+    unsafe {
+        let previous = gicc.read(GICC_PMR) as u8;
+        if ceiling < previous {
+            gicc.write(GICC_PMR, u32::from(ceiling));
+            // Reading the mask back makes sure the write has reached the CPU
+            // Interface before we go on
+            let _ = gicc.read(GICC_PMR);
+            crate::asm::dsb();
+            crate::asm::isb();
+        }
+        previous
+    }
+}
+
+/// Raise the priority mask of the CPU Interface given to
+/// [`Gic::set_critical_section_ceiling`] to the ceiling
+///
+/// Returns the previous mask, or `None` if no CPU Interface has been given.
+#[cfg(all(arm_architecture = "v7-r", feature = "critical-section-gic-priority"))]
+pub(crate) fn critical_section_acquire() -> Option<u8> {
+    let gicc = CRITICAL_SECTION_GICC.load(Ordering::Acquire);
+    if gicc.is_null() {
+        return None;
+    }
+    let ceiling = CRITICAL_SECTION_CEILING.load(Ordering::Relaxed);
+    // Safety: the address came from a `Gic`, which was given it in `new`
+    Some(unsafe { raise_priority_mask(Mmio(gicc), ceiling) })
+}
+
+/// Put back the priority mask returned by [`critical_section_acquire`]
+///
+/// Returns `false` (and does nothing) if no CPU Interface has been given.
+#[cfg(all(arm_architecture = "v7-r", feature = "critical-section-gic-priority"))]
+pub(crate) fn critical_section_release(previous: u8) -> bool {
+    let gicc = CRITICAL_SECTION_GICC.load(Ordering::Acquire);
+    if gicc.is_null() {
+        return false;
+    }
+    // Safety: the address came from a `Gic`, which was given it in `new`
+    unsafe {
+        let gicc = Mmio(gicc);
+        if gicc.read(GICC_PMR) as u8 != previous {
+            gicc.write(GICC_PMR, u32::from(previous));
+        }
+    }
+    true
+}
+
+impl InterruptController for Gic {
+    type Id = IntId;
+
+    fn enable(&mut self, id: IntId) {
+        self.enable_interrupt(id, true);
+    }
+
+    fn disable(&mut self, id: IntId) {
+        self.enable_interrupt(id, false);
+    }
+
+    fn set_pending(&mut self, id: IntId, pending: bool) {
+        Gic::set_pending(self, id, pending);
+    }
+
+    fn set_priority(&mut self, id: IntId, priority: u8) {
+        Gic::set_priority(self, id, priority);
+    }
+
+    fn acknowledge(&self) -> Option<IntId> {
+        Gic::acknowledge(self)
+    }
+
+    fn end_interrupt(&self, id: IntId) {
+        Gic::end_interrupt(self, id);
+    }
+}
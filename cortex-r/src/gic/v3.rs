@@ -0,0 +1,400 @@
+//! A driver for the GICv3 Distributor and Redistributors
+//!
+//! On the Cortex-R52, the Distributor (GICD) lives at PERIPHBASE, and the
+//! Redistributors (GICR), one per core, follow it. We find PERIPHBASE by
+//! reading CBAR, and find the Redistributor for this core by matching its
+//! affinity against MPIDR.
+//!
+//! SGIs and PPIs are configured through this core's Redistributor, and SPIs
+//! through the Distributor. Use [`super::cpu`] to acknowledge interrupts.
+
+use super::{IntId, Mmio};
+
+#[doc(inline)]
+pub use super::{Group, Trigger};
+use crate::interrupt::InterruptController;
+use crate::register::{Cbar, Mpidr};
+
+/// Offset from PERIPHBASE to the Distributor on the Cortex-R52
+const GICD_OFFSET: usize = 0x0000_0000;
+
+/// Offset from PERIPHBASE to the first Redistributor on the Cortex-R52
+const GICR_OFFSET: usize = 0x0010_0000;
+
+/// The size of each Redistributor (an RD_base frame plus an SGI_base frame)
+const GICR_STRIDE: usize = 0x0002_0000;
+
+/// Offset from RD_base to SGI_base
+const GICR_SGI_OFFSET: usize = 0x0001_0000;
+
+// Distributor registers
+const GICD_CTLR: usize = 0x0000;
+const GICD_TYPER: usize = 0x0004;
+const GICD_IGROUPR: usize = 0x0080;
+const GICD_ISENABLER: usize = 0x0100;
+const GICD_ICENABLER: usize = 0x0180;
+const GICD_ISPENDR: usize = 0x0200;
+const GICD_ICPENDR: usize = 0x0280;
+const GICD_IPRIORITYR: usize = 0x0400;
+const GICD_ICFGR: usize = 0x0C00;
+const GICD_IROUTER: usize = 0x6000;
+
+const GICD_CTLR_ENABLE_GRP0: u32 = 1 << 0;
+const GICD_CTLR_ENABLE_GRP1: u32 = 1 << 1;
+const GICD_CTLR_ARE: u32 = 1 << 4;
+const GICD_CTLR_RWP: u32 = 1 << 31;
+
+// Redistributor registers, in the RD_base frame
+const GICR_CTLR: usize = 0x0000;
+const GICR_TYPER: usize = 0x0008;
+const GICR_WAKER: usize = 0x0014;
+
+const GICR_CTLR_RWP: u32 = 1 << 3;
+const GICR_TYPER_LAST: u32 = 1 << 4;
+const GICR_WAKER_PROCESSOR_SLEEP: u32 = 1 << 1;
+const GICR_WAKER_CHILDREN_ASLEEP: u32 = 1 << 2;
+
+// Redistributor registers, in the SGI_base frame
+const GICR_IGROUPR0: usize = 0x0080;
+const GICR_ISENABLER0: usize = 0x0100;
+const GICR_ICENABLER0: usize = 0x0180;
+const GICR_ISPENDR0: usize = 0x0200;
+const GICR_ICPENDR0: usize = 0x0280;
+const GICR_IPRIORITYR: usize = 0x0400;
+const GICR_ICFGR0: usize = 0x0C00;
+
+/// The affinity of a core, as used for routing SPIs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Affinity {
+    /// Affinity level 3
+    pub aff3: u8,
+    /// Affinity level 2
+    pub aff2: u8,
+    /// Affinity level 1
+    pub aff1: u8,
+    /// Affinity level 0
+    pub aff0: u8,
+}
+
+impl Affinity {
+    /// The affinity of this core, from MPIDR
+    pub fn current() -> Affinity {
+        let mpidr = Mpidr::read();
+        Affinity {
+            aff3: 0,
+            aff2: mpidr.aff2(),
+            aff1: mpidr.aff1(),
+            aff0: mpidr.aff0(),
+        }
+    }
+
+    /// Pack into the format used by GICR_TYPER[63:32]
+    const fn to_bits(self) -> u32 {
+        ((self.aff3 as u32) << 24)
+            | ((self.aff2 as u32) << 16)
+            | ((self.aff1 as u32) << 8)
+            | (self.aff0 as u32)
+    }
+}
+
+/// Which core(s) an SPI is delivered to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Routing {
+    /// The core with the given affinity
+    Core(Affinity),
+    /// Any one core which is participating
+    Any,
+}
+
+/// A driver for a GICv3 Distributor, and the Redistributor for this core
+pub struct Gic {
+    gicd: Mmio,
+    gicr: Mmio,
+}
+
+// Safety: the driver only holds the addresses of the GIC. Anything which
+// changes the configuration of the GIC takes `&mut self`, and the rest is
+// single register accesses.
+unsafe impl Send for Gic {}
+unsafe impl Sync for Gic {}
+
+impl Gic {
+    /// Create a driver from the Distributor and this core's Redistributor
+    ///
+    /// # Safety
+    ///
+    /// The addresses must point at a GICv3 Distributor and at the RD_base
+    /// frame of the Redistributor for this core. There must only be one
+    /// driver for them at any one time.
+    pub unsafe fn new(gicd: *mut u32, gicr: *mut u32) -> Gic {
+        Gic {
+            gicd: Mmio(gicd),
+            gicr: Mmio(gicr),
+        }
+    }
+
+    /// Create a driver for the GIC in a Cortex-R52, found using CBAR
+    ///
+    /// Returns `None` if no Redistributor matches this core's affinity.
+    ///
+    /// # Safety
+    ///
+    /// This must be a Cortex-R52 (or similar) with the standard GIC layout.
+    /// There must only be one driver for the GIC at any one time.
+    pub unsafe fn from_cbar() -> Option<Gic> {
+        let periphbase = Cbar::read().periphbase();
+        let gicd = periphbase.wrapping_byte_add(GICD_OFFSET);
+        let affinity = Affinity::current().to_bits();
+        let mut gicr = Mmio(periphbase.wrapping_byte_add(GICR_OFFSET));
+        loop {
+            // Safety: we're walking the Redistributors, which the caller
+            // promised exist
+            let (typer_lo, typer_hi) =
+                unsafe { (gicr.read(GICR_TYPER), gicr.read(GICR_TYPER + 4)) };
+            if typer_hi == affinity {
+                // Safety: passed on to our caller
+                return Some(unsafe { Gic::new(gicd, gicr.0) });
+            }
+            if (typer_lo & GICR_TYPER_LAST) != 0 {
+                return None;
+            }
+            gicr = Mmio(gicr.0.wrapping_byte_add(GICR_STRIDE));
+        }
+    }
+
+    /// The number of interrupt IDs the Distributor supports (including SGIs and
+    /// PPIs)
+    pub fn num_interrupts(&self) -> u32 {
+        // Safety: the address was given to us in `new`
+        let typer = unsafe { self.gicd.read(GICD_TYPER) };
+        (((typer & 0x1F) + 1) * 32).min(IntId::SPECIAL_START)
+    }
+
+    /// Initialise the Distributor and this core's Redistributor and CPU
+    /// interface
+    ///
+    /// Wakes up the Redistributor, enables affinity routing and both interrupt
+    /// groups, and puts every interrupt in Group 1, disabled, at the lowest
+    /// priority (0xFF). Then enables both groups in the CPU interface, with a
+    /// priority mask that lets through every interrupt with a higher
+    /// priority than that - so remember to set a priority for each interrupt
+    /// you enable.
+    pub fn init(&mut self) {
+        // Safety: the addresses were given to us in `new`
+        unsafe {
+            // Wake up the Redistributor
+            self.gicr
+                .modify(GICR_WAKER, |w| w & !GICR_WAKER_PROCESSOR_SLEEP);
+            while (self.gicr.read(GICR_WAKER) & GICR_WAKER_CHILDREN_ASLEEP) != 0 {
+                core::hint::spin_loop();
+            }
+
+            // Enable the Distributor, with affinity routing
+            self.gicd.write(GICD_CTLR, GICD_CTLR_ARE);
+            self.wait_for_gicd();
+            self.gicd.write(
+                GICD_CTLR,
+                GICD_CTLR_ARE | GICD_CTLR_ENABLE_GRP0 | GICD_CTLR_ENABLE_GRP1,
+            );
+            self.wait_for_gicd();
+
+            // Set up the SGIs and PPIs
+            let sgi_base = self.sgi_base();
+            sgi_base.write(GICR_ICENABLER0, 0xFFFF_FFFF);
+            self.wait_for_gicr();
+            sgi_base.write(GICR_IGROUPR0, 0xFFFF_FFFF);
+            for offset in (0..IntId::SPI_START as usize).step_by(4) {
+                sgi_base.write(GICR_IPRIORITYR + offset, 0xFFFF_FFFF);
+            }
+
+            // Set up the SPIs
+            let num_interrupts = self.num_interrupts() as usize;
+            for id in (IntId::SPI_START as usize..num_interrupts).step_by(32) {
+                self.gicd.write(GICD_ICENABLER + id / 8, 0xFFFF_FFFF);
+                self.gicd.write(GICD_IGROUPR + id / 8, 0xFFFF_FFFF);
+            }
+            self.wait_for_gicd();
+            for id in (IntId::SPI_START as usize..num_interrupts).step_by(4) {
+                self.gicd.write(GICD_IPRIORITYR + id, 0xFFFF_FFFF);
+            }
+        }
+
+        super::cpu::set_priority_mask(0xFF);
+        super::cpu::enable_group0(true);
+        super::cpu::enable_group1(true);
+    }
+
+    /// Enable or disable an interrupt
+    pub fn enable_interrupt(&mut self, intid: IntId, enable: bool) {
+        let (regs, offset) = if enable {
+            self.regs_for(intid, GICR_ISENABLER0, GICD_ISENABLER)
+        } else {
+            self.regs_for(intid, GICR_ICENABLER0, GICD_ICENABLER)
+        };
+        // Safety: the addresses were given to us in `new`
+        unsafe {
+            regs.write_bit(offset, intid);
+            if intid.is_spi() {
+                self.wait_for_gicd();
+            } else {
+                self.wait_for_gicr();
+            }
+        }
+    }
+
+    /// Set or clear the pending state of an interrupt
+    pub fn set_pending(&mut self, intid: IntId, pending: bool) {
+        let (regs, offset) = if pending {
+            self.regs_for(intid, GICR_ISPENDR0, GICD_ISPENDR)
+        } else {
+            self.regs_for(intid, GICR_ICPENDR0, GICD_ICPENDR)
+        };
+        // Safety: the addresses were given to us in `new`
+        unsafe {
+            regs.write_bit(offset, intid);
+        }
+    }
+
+    /// Set the priority of an interrupt
+    ///
+    /// Lower values are higher priority. The GIC may ignore some of the low
+    /// bits.
+    pub fn set_priority(&mut self, intid: IntId, priority: u8) {
+        let (regs, offset) = self.regs_for(intid, GICR_IPRIORITYR, GICD_IPRIORITYR);
+        // Safety: the addresses were given to us in `new`
+        unsafe {
+            regs.write_u8(offset + intid.to_raw() as usize, priority);
+        }
+    }
+
+    /// Get the priority of an interrupt
+    pub fn priority(&self, intid: IntId) -> u8 {
+        let (regs, offset) = self.regs_for(intid, GICR_IPRIORITYR, GICD_IPRIORITYR);
+        // Safety: the addresses were given to us in `new`
+        unsafe { regs.read_u8(offset + intid.to_raw() as usize) }
+    }
+
+    /// Set up [`DEFERRED_SGI`](super::DEFERRED_SGI) for deferred work
+    ///
+    /// Gives it the lowest priority that the priority mask from [`Gic::init`]
+    /// lets through, and enables it. Give it a handler (e.g. with
+    /// `cortex_r_rt::register_deferred`) before calling
+    /// [`super::cpu::pend_deferred`].
+    pub fn enable_deferred(&mut self) {
+        let intid = super::DEFERRED_SGI;
+        self.set_priority(intid, 0xFF);
+        let priority = super::lowest_unmasked_priority(self.priority(intid));
+        self.set_priority(intid, priority);
+        self.enable_interrupt(intid, true);
+    }
+
+    /// Set which group an interrupt belongs to
+    pub fn set_group(&mut self, intid: IntId, group: Group) {
+        let (regs, offset) = self.regs_for(intid, GICR_IGROUPR0, GICD_IGROUPR);
+        // Safety: the addresses were given to us in `new`
+        unsafe {
+            regs.modify_bit(offset, intid, group == Group::Group1);
+        }
+    }
+
+    /// Set whether an interrupt is level or edge triggered
+    ///
+    /// SGIs are always edge triggered, and this does nothing for them.
+    pub fn set_trigger(&mut self, intid: IntId, trigger: Trigger) {
+        if intid.is_sgi() {
+            return;
+        }
+        let (regs, offset) = self.regs_for(intid, GICR_ICFGR0, GICD_ICFGR);
+        let id = intid.to_raw() as usize;
+        let offset = offset + (id / 16) * 4;
+        let bit = 1 << ((id % 16) * 2 + 1);
+        // Safety: the addresses were given to us in `new`
+        unsafe {
+            regs.modify(offset, |w| match trigger {
+                Trigger::Level => w & !bit,
+                Trigger::Edge => w | bit,
+            });
+        }
+    }
+
+    /// Set which core(s) an SPI is delivered to
+    ///
+    /// Panics if `intid` is not an SPI.
+    pub fn set_routing(&mut self, intid: IntId, routing: Routing) {
+        assert!(intid.is_spi(), "only SPIs can be routed");
+        let (lo, hi) = match routing {
+            Routing::Core(affinity) => {
+                let bits = affinity.to_bits();
+                (bits & 0x00FF_FFFF, bits >> 24)
+            }
+            Routing::Any => (1 << 31, 0),
+        };
+        let offset = GICD_IROUTER + intid.to_raw() as usize * 8;
+        // Safety: the address was given to us in `new`
+        unsafe {
+            self.gicd.write(offset, lo);
+            self.gicd.write(offset + 4, hi);
+        }
+    }
+
+    /// Pick the Redistributor SGI_base frame for SGIs and PPIs, or the
+    /// Distributor for SPIs, along with the matching register offset
+    fn regs_for(&self, intid: IntId, gicr_offset: usize, gicd_offset: usize) -> (Mmio, usize) {
+        if intid.is_spi() {
+            (self.gicd, gicd_offset)
+        } else {
+            (self.sgi_base(), gicr_offset)
+        }
+    }
+
+    /// Get the SGI_base frame of this core's Redistributor
+    fn sgi_base(&self) -> Mmio {
+        Mmio(self.gicr.0.wrapping_byte_add(GICR_SGI_OFFSET))
+    }
+
+    /// Wait for a write to GICD_CTLR or GICD_ICENABLER to take effect
+    unsafe fn wait_for_gicd(&self) {
+        while (unsafe { self.gicd.read(GICD_CTLR) } & GICD_CTLR_RWP) != 0 {
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Wait for a write to GICR_ICENABLER0 to take effect
+    unsafe fn wait_for_gicr(&self) {
+        while (unsafe { self.gicr.read(GICR_CTLR) } & GICR_CTLR_RWP) != 0 {
+            core::hint::spin_loop();
+        }
+    }
+}
+
+/// Acknowledging and ending interrupts goes through the CPU interface of the
+/// current core (see [`super::cpu`]), and only handles Group 1 interrupts.
+impl InterruptController for Gic {
+    type Id = IntId;
+
+    fn enable(&mut self, id: IntId) {
+        self.enable_interrupt(id, true);
+    }
+
+    fn disable(&mut self, id: IntId) {
+        self.enable_interrupt(id, false);
+    }
+
+    fn set_pending(&mut self, id: IntId, pending: bool) {
+        Gic::set_pending(self, id, pending);
+    }
+
+    fn set_priority(&mut self, id: IntId, priority: u8) {
+        Gic::set_priority(self, id, priority);
+    }
+
+    fn acknowledge(&self) -> Option<IntId> {
+        super::cpu::acknowledge()
+    }
+
+    fn end_interrupt(&self, id: IntId) {
+        super::cpu::end_interrupt(id);
+    }
+}
@@ -39,17 +39,91 @@ pub fn disable() {
 
 /// Run with interrupts disabled
 ///
+/// The closure is given a [`CriticalSection`] token, which proves that IRQs
+/// are masked, and which can be used to borrow the contents of a [`Mutex`].
+///
 /// * Doesn't work in User mode.
 /// * Doesn't disable FIQ.
 #[inline]
 pub fn free<F, T>(f: F) -> T
 where
-    F: FnOnce() -> T,
+    F: FnOnce(&CriticalSection) -> T,
+{
+    let cpsr = crate::register::Cpsr::read();
+    disable();
+    // Safety: IRQs are masked until after the closure returns
+    let result = f(unsafe { &CriticalSection::new() });
+    // the i bit means "masked"
+    if !cpsr.i() {
+        // Safety: We're only turning them back on if they were on previously
+        unsafe {
+            enable();
+        }
+    }
+    result
+}
+
+/// Enable FIQ
+///
+/// * Doesn't work in User mode.
+/// * Doesn't enable IRQ.
+///
+/// # Safety
+///
+/// Do not call this function inside a critical section that masks FIQ
+#[inline]
+pub unsafe fn enable_fiq() {
+    // Ensure no preceeding memory accesses are reordered to after FIQ is enabled.
+    compiler_fence(Ordering::SeqCst);
+    // Safety: We're atomically setting a bit in a special register, and we're
+    // in an unsafe function that places restrictions on when you can call it
+    #[cfg(target_arch = "arm")]
+    unsafe {
+        core::arch::asm!("dsb", "cpsie f", options(nomem, nostack, preserves_flags));
+    };
+}
+
+/// Disable FIQ
+///
+/// * Doesn't work in User mode.
+/// * Doesn't disable IRQ.
+/// * Has no effect if SCTLR.NMFI is set, as FIQ is then non-maskable.
+#[inline]
+pub fn disable_fiq() {
+    // Safety: We're atomically clearing a bit in a special register
+    #[cfg(target_arch = "arm")]
+    unsafe {
+        core::arch::asm!("cpsid f", "dsb", options(nomem, nostack, preserves_flags));
+    };
+    // Ensure no subsequent memory accesses are reordered to before FIQ is disabled.
+    compiler_fence(Ordering::SeqCst);
+}
+
+/// Run with both IRQ and FIQ disabled
+///
+/// Use this instead of [`free`] for anything shared with an FIQ handler.
+/// Each of IRQ and FIQ is only enabled again afterwards if it was enabled
+/// before.
+///
+/// * Doesn't work in User mode.
+#[inline]
+pub fn free_all<F, T>(f: F) -> T
+where
+    F: FnOnce(&CriticalSection) -> T,
 {
     let cpsr = crate::register::Cpsr::read();
+    disable_fiq();
     disable();
-    let result = f();
-    if cpsr.i() {
+    // Safety: IRQs are masked until after the closure returns
+    let result = f(unsafe { &CriticalSection::new() });
+    // the i and f bits mean "masked"
+    if !cpsr.f() {
+        // Safety: We're only turning it back on if it was on previously
+        unsafe {
+            enable_fiq();
+        }
+    }
+    if !cpsr.i() {
         // Safety: We're only turning them back on if they were on previously
         unsafe {
             enable();
@@ -57,3 +131,137 @@ where
     }
     result
 }
+
+/// A token which proves that IRQs are masked
+///
+/// You get one from [`free`]. It only proves IRQs are masked on the current
+/// core, so a [`Mutex`] is only a real lock on a single-core system.
+pub struct CriticalSection {
+    _0: (),
+}
+
+impl CriticalSection {
+    /// Create a critical section token
+    ///
+    /// # Safety
+    ///
+    /// IRQs must be masked for as long as the token (or any reference to it)
+    /// exists.
+    #[inline]
+    pub unsafe fn new() -> CriticalSection {
+        CriticalSection { _0: () }
+    }
+
+    /// Get a token from a `critical-section` crate critical section
+    ///
+    /// Our implementation of that crate masks IRQs (and with the
+    /// `critical-section-single-core-fiq` feature, FIQ too), so this is the
+    /// same proof.
+    #[cfg(feature = "critical-section-single-core")]
+    #[inline]
+    pub fn from_critical_section<'cs>(
+        _cs: ::critical_section::CriticalSection<'cs>,
+    ) -> &'cs CriticalSection {
+        &CriticalSection { _0: () }
+    }
+}
+
+/// A token from [`free`] is also proof of a `critical-section` crate critical
+/// section, as our implementation of that crate only masks IRQs.
+///
+/// With the `critical-section-single-core-fiq` feature, that crate's critical
+/// sections also mask FIQ, which our tokens don't prove, so this is not
+/// available.
+#[cfg(all(
+    feature = "critical-section-single-core",
+    not(feature = "critical-section-single-core-fiq")
+))]
+impl<'cs> From<&'cs CriticalSection> for ::critical_section::CriticalSection<'cs> {
+    #[inline]
+    fn from(_cs: &'cs CriticalSection) -> Self {
+        // Safety: IRQs are masked for as long as `_cs` exists
+        unsafe { ::critical_section::CriticalSection::new() }
+    }
+}
+
+/// A value which can only be accessed with IRQs masked
+///
+/// Borrow the value with the [`CriticalSection`] token from [`free`]. For
+/// mutation, use a `Mutex<Cell<T>>` or a `Mutex<RefCell<T>>`.
+///
+/// This is only sound on a single-core system, because masking IRQs on one
+/// core does not stop another core from accessing the value.
+pub struct Mutex<T> {
+    inner: core::cell::UnsafeCell<T>,
+}
+
+impl<T> Mutex<T> {
+    /// Create a new mutex holding the given value
+    #[inline]
+    pub const fn new(value: T) -> Mutex<T> {
+        Mutex {
+            inner: core::cell::UnsafeCell::new(value),
+        }
+    }
+
+    /// Borrow the value, while IRQs are masked
+    #[inline]
+    pub fn borrow<'cs>(&'cs self, _cs: &'cs CriticalSection) -> &'cs T {
+        // Safety: no IRQ handler can run while `_cs` exists, so nothing else
+        // has a reference that could be used to modify the value
+        unsafe { &*self.inner.get() }
+    }
+
+    /// Get a mutable reference to the value
+    ///
+    /// We have the mutex exclusively, so no critical section is needed.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.inner.get_mut()
+    }
+
+    /// Take the value out of the mutex
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner()
+    }
+}
+
+// Safety: the value can only be borrowed with IRQs masked, which on a
+// single-core system is all it takes to stop anything else accessing it
+unsafe impl<T: Send> Sync for Mutex<T> {}
+
+/// An interrupt controller, such as a GIC or a VIC
+///
+/// This covers what a driver needs to manage its own interrupt, and what an
+/// IRQ handler needs to dispatch interrupts, without knowing which controller
+/// the SoC has. Anything more specific (e.g. trigger modes, or routing to
+/// other cores) is done with the controller's own driver.
+pub trait InterruptController {
+    /// The type which identifies an interrupt on this controller
+    type Id: Copy + PartialEq + core::fmt::Debug;
+
+    /// Enable an interrupt
+    fn enable(&mut self, id: Self::Id);
+
+    /// Disable an interrupt
+    fn disable(&mut self, id: Self::Id);
+
+    /// Set or clear the pending state of an interrupt
+    fn set_pending(&mut self, id: Self::Id, pending: bool);
+
+    /// Set the priority of an interrupt
+    ///
+    /// Lower values are higher priority. A controller may ignore some of the
+    /// low bits, or may not support priorities at all.
+    fn set_priority(&mut self, id: Self::Id, priority: u8);
+
+    /// Acknowledge the highest priority pending interrupt
+    ///
+    /// Returns `None` if no interrupt is pending. Otherwise, you must call
+    /// [`InterruptController::end_interrupt`] once it has been handled.
+    fn acknowledge(&self) -> Option<Self::Id>;
+
+    /// Tell the controller an acknowledged interrupt has been handled
+    fn end_interrupt(&self, id: Self::Id);
+}
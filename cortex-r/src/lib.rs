@@ -5,12 +5,45 @@
 #[cfg(feature = "critical-section-single-core")]
 mod critical_section;
 
+#[cfg(all(
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r"),
+    feature = "critical-section-gic-priority"
+))]
+mod critical_section_gic;
+
+#[cfg(all(
+    feature = "critical-section-single-core",
+    feature = "critical-section-gic-priority"
+))]
+compile_error!("Only one critical-section implementation can be enabled");
+
 pub mod register;
 
 pub mod interrupt;
 
 pub mod asm;
 
+#[cfg(any(arm_architecture = "v7-r", arm_architecture = "v8-r"))]
+pub mod abort;
+
+pub mod cache;
+
+pub mod dma;
+
+pub mod gic;
+
+pub mod mpu;
+
+pub mod pmu;
+
+#[cfg(all(
+    target_arch = "arm",
+    any(arm_architecture = "v7-r", arm_architecture = "v8-r")
+))]
+pub mod probe;
+
+pub mod vic;
+
 /// Generate an SVC call with the given argument.
 ///
 /// Safe to call even in Supervisor (Svc) mode, as long as your Svc handler
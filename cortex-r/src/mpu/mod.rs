@@ -0,0 +1,11 @@
+//! Support for the Memory Protection Unit (MPU)
+//!
+//! Armv7-R processors implement the Protected Memory System Architecture
+//! version 7 (PMSAv7), which is supported by the `v7` module. Armv8-R
+//! processors implement PMSAv8-32, which is supported by the `v8` module.
+
+#[cfg(arm_architecture = "v7-r")]
+pub mod v7;
+
+#[cfg(arm_architecture = "v8-r")]
+pub mod v8;
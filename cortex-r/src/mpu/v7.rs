@@ -0,0 +1,684 @@
+//! Support for the PMSAv7 MPU found on Armv7-R processors
+//!
+//! Processors like the Cortex-R4 and Cortex-R5 have an MPU with up to 16
+//! unified regions. Each region covers a power-of-two sized block of memory
+//! aligned to its size, and where regions overlap, the highest numbered region
+//! wins.
+
+use arbitrary_int::{u3, u5};
+
+use crate::register::{Dracr, Drbar, Drsr, Mpuir, Rgnr, Sctlr};
+
+/// The ways in which programming the MPU can fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// This MPU does not have a region with the given number
+    InvalidRegion(u8),
+    /// The base address is not aligned to the size of the region
+    UnalignedBase(u32),
+    /// Subregions can only be disabled in regions of 256 bytes or more
+    SubregionsUnsupported,
+}
+
+/// The size of an MPU region
+///
+/// The discriminant is the value programmed into the RSIZE field of DRSR.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum RegionSize {
+    /// 32 bytes
+    B32 = 4,
+    /// 64 bytes
+    B64 = 5,
+    /// 128 bytes
+    B128 = 6,
+    /// 256 bytes
+    B256 = 7,
+    /// 512 bytes
+    B512 = 8,
+    /// 1 KiB
+    K1 = 9,
+    /// 2 KiB
+    K2 = 10,
+    /// 4 KiB
+    K4 = 11,
+    /// 8 KiB
+    K8 = 12,
+    /// 16 KiB
+    K16 = 13,
+    /// 32 KiB
+    K32 = 14,
+    /// 64 KiB
+    K64 = 15,
+    /// 128 KiB
+    K128 = 16,
+    /// 256 KiB
+    K256 = 17,
+    /// 512 KiB
+    K512 = 18,
+    /// 1 MiB
+    M1 = 19,
+    /// 2 MiB
+    M2 = 20,
+    /// 4 MiB
+    M4 = 21,
+    /// 8 MiB
+    M8 = 22,
+    /// 16 MiB
+    M16 = 23,
+    /// 32 MiB
+    M32 = 24,
+    /// 64 MiB
+    M64 = 25,
+    /// 128 MiB
+    M128 = 26,
+    /// 256 MiB
+    M256 = 27,
+    /// 512 MiB
+    M512 = 28,
+    /// 1 GiB
+    G1 = 29,
+    /// 2 GiB
+    G2 = 30,
+    /// 4 GiB
+    G4 = 31,
+}
+
+impl RegionSize {
+    /// Every region size, from smallest to largest
+    const ALL: [RegionSize; 28] = [
+        RegionSize::B32,
+        RegionSize::B64,
+        RegionSize::B128,
+        RegionSize::B256,
+        RegionSize::B512,
+        RegionSize::K1,
+        RegionSize::K2,
+        RegionSize::K4,
+        RegionSize::K8,
+        RegionSize::K16,
+        RegionSize::K32,
+        RegionSize::K64,
+        RegionSize::K128,
+        RegionSize::K256,
+        RegionSize::K512,
+        RegionSize::M1,
+        RegionSize::M2,
+        RegionSize::M4,
+        RegionSize::M8,
+        RegionSize::M16,
+        RegionSize::M32,
+        RegionSize::M64,
+        RegionSize::M128,
+        RegionSize::M256,
+        RegionSize::M512,
+        RegionSize::G1,
+        RegionSize::G2,
+        RegionSize::G4,
+    ];
+
+    /// Get the region size that is `2 ** log2` bytes long, if there is one
+    pub const fn from_log2(log2: u32) -> Option<RegionSize> {
+        if log2 < 5 || log2 > 32 {
+            None
+        } else {
+            Some(Self::ALL[(log2 - 5) as usize])
+        }
+    }
+
+    /// Get the smallest region size that is at least `bytes` long
+    ///
+    /// Returns `None` if `bytes` is larger than 4 GiB.
+    pub const fn at_least(bytes: u64) -> Option<RegionSize> {
+        if bytes <= 32 {
+            return Some(RegionSize::B32);
+        }
+        match bytes.checked_next_power_of_two() {
+            Some(size) => Self::from_log2(size.trailing_zeros()),
+            None => None,
+        }
+    }
+
+    /// The base-2 logarithm of the size of the region, in bytes
+    pub const fn log2(self) -> u32 {
+        self as u32 + 1
+    }
+
+    /// The size of the region, in bytes
+    pub const fn bytes(self) -> u64 {
+        1 << self.log2()
+    }
+}
+
+/// Access permissions for an MPU region
+///
+/// The discriminant is the value programmed into the AP field of DRACR. PL1 is
+/// any privileged mode; PL0 is User mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum AccessPermissions {
+    /// No access at any privilege level
+    NoAccess = 0b000,
+    /// Read/write at PL1, no access at PL0
+    PrivReadWrite = 0b001,
+    /// Read/write at PL1, read-only at PL0
+    PrivReadWriteUserReadOnly = 0b010,
+    /// Read/write at any privilege level
+    ReadWrite = 0b011,
+    /// Read-only at PL1, no access at PL0
+    PrivReadOnly = 0b101,
+    /// Read-only at any privilege level
+    ReadOnly = 0b110,
+}
+
+impl AccessPermissions {
+    /// Decode the AP field of DRACR
+    const fn from_bits(bits: u3) -> Option<AccessPermissions> {
+        match bits.value() {
+            0b000 => Some(AccessPermissions::NoAccess),
+            0b001 => Some(AccessPermissions::PrivReadWrite),
+            0b010 => Some(AccessPermissions::PrivReadWriteUserReadOnly),
+            0b011 => Some(AccessPermissions::ReadWrite),
+            0b101 => Some(AccessPermissions::PrivReadOnly),
+            0b110 | 0b111 => Some(AccessPermissions::ReadOnly),
+            _ => None,
+        }
+    }
+}
+
+/// The cache policy for one level of a Normal memory region
+///
+/// The discriminant is the two-bit encoding used in the TEX, C and B fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum CachePolicy {
+    /// Non-cacheable
+    NonCacheable = 0b00,
+    /// Write-back, write-allocate
+    WriteBackWriteAllocate = 0b01,
+    /// Write-through, no write-allocate
+    WriteThroughNoWriteAllocate = 0b10,
+    /// Write-back, no write-allocate
+    WriteBackNoWriteAllocate = 0b11,
+}
+
+impl CachePolicy {
+    /// Decode a two-bit cache policy
+    const fn from_bits(bits: u8) -> CachePolicy {
+        match bits & 0b11 {
+            0b00 => CachePolicy::NonCacheable,
+            0b01 => CachePolicy::WriteBackWriteAllocate,
+            0b10 => CachePolicy::WriteThroughNoWriteAllocate,
+            _ => CachePolicy::WriteBackNoWriteAllocate,
+        }
+    }
+}
+
+/// The memory type of an MPU region
+///
+/// This is encoded into the TEX, C, B and S fields of DRACR.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MemAttr {
+    /// Strongly-ordered memory (always shareable)
+    StronglyOrdered,
+    /// Device memory
+    Device {
+        /// Is the region shareable?
+        shareable: bool,
+    },
+    /// Normal memory
+    Normal {
+        /// The outer cache policy
+        outer: CachePolicy,
+        /// The inner cache policy
+        inner: CachePolicy,
+        /// Is the region shareable?
+        shareable: bool,
+    },
+}
+
+impl MemAttr {
+    /// Set the TEX, C, B and S fields in a DRACR value
+    const fn encode(self, dracr: Dracr) -> Dracr {
+        match self {
+            MemAttr::StronglyOrdered => dracr
+                .with_tex(u3::new(0b000))
+                .with_c(false)
+                .with_b(false)
+                .with_s(false),
+            MemAttr::Device { shareable: true } => dracr
+                .with_tex(u3::new(0b000))
+                .with_c(false)
+                .with_b(true)
+                .with_s(false),
+            MemAttr::Device { shareable: false } => dracr
+                .with_tex(u3::new(0b010))
+                .with_c(false)
+                .with_b(false)
+                .with_s(false),
+            MemAttr::Normal {
+                outer,
+                inner,
+                shareable,
+            } => dracr
+                .with_tex(u3::new(0b100 | outer as u8))
+                .with_c((inner as u8 & 0b10) != 0)
+                .with_b((inner as u8 & 0b01) != 0)
+                .with_s(shareable),
+        }
+    }
+
+    /// Decode the TEX, C, B and S fields of a DRACR value
+    const fn decode(dracr: &Dracr) -> Option<MemAttr> {
+        let tex = dracr.tex().value();
+        let cb = ((dracr.c() as u8) << 1) | (dracr.b() as u8);
+        let shareable = dracr.s();
+        if (tex & 0b100) != 0 {
+            return Some(MemAttr::Normal {
+                outer: CachePolicy::from_bits(tex),
+                inner: CachePolicy::from_bits(cb),
+                shareable,
+            });
+        }
+        match (tex, cb) {
+            (0b000, 0b00) => Some(MemAttr::StronglyOrdered),
+            (0b000, 0b01) => Some(MemAttr::Device { shareable: true }),
+            (0b000, 0b10) => Some(MemAttr::Normal {
+                outer: CachePolicy::WriteThroughNoWriteAllocate,
+                inner: CachePolicy::WriteThroughNoWriteAllocate,
+                shareable,
+            }),
+            (0b000, 0b11) => Some(MemAttr::Normal {
+                outer: CachePolicy::WriteBackNoWriteAllocate,
+                inner: CachePolicy::WriteBackNoWriteAllocate,
+                shareable,
+            }),
+            (0b001, 0b00) => Some(MemAttr::Normal {
+                outer: CachePolicy::NonCacheable,
+                inner: CachePolicy::NonCacheable,
+                shareable,
+            }),
+            (0b001, 0b11) => Some(MemAttr::Normal {
+                outer: CachePolicy::WriteBackWriteAllocate,
+                inner: CachePolicy::WriteBackWriteAllocate,
+                shareable,
+            }),
+            (0b010, 0b00) => Some(MemAttr::Device { shareable: false }),
+            _ => None,
+        }
+    }
+}
+
+/// Describes a PMSAv7 MPU region
+///
+/// Build one with [`Region::new`] and the `with_` methods. The defaults are
+/// the most restrictive settings: Strongly-ordered, read/write only from PL1,
+/// and never executable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Region {
+    base: u32,
+    size: RegionSize,
+    subregion_disable: u8,
+    mem_attr: MemAttr,
+    access: AccessPermissions,
+    no_exec: bool,
+}
+
+impl Region {
+    /// Create a new region description
+    pub const fn new(base: u32, size: RegionSize) -> Region {
+        Region {
+            base,
+            size,
+            subregion_disable: 0,
+            mem_attr: MemAttr::StronglyOrdered,
+            access: AccessPermissions::PrivReadWrite,
+            no_exec: true,
+        }
+    }
+
+    /// Disable some of the eight subregions of this region
+    ///
+    /// Set bit N to exclude subregion N from the region.
+    pub const fn with_subregion_disable(self, mask: u8) -> Region {
+        Region {
+            subregion_disable: mask,
+            ..self
+        }
+    }
+
+    /// Set the memory type of this region
+    pub const fn with_mem_attr(self, mem_attr: MemAttr) -> Region {
+        Region { mem_attr, ..self }
+    }
+
+    /// Set the access permissions for this region
+    pub const fn with_access(self, access: AccessPermissions) -> Region {
+        Region { access, ..self }
+    }
+
+    /// Set whether instructions can be fetched from this region
+    pub const fn with_no_exec(self, no_exec: bool) -> Region {
+        Region { no_exec, ..self }
+    }
+
+    /// The base address of this region
+    pub const fn base(&self) -> u32 {
+        self.base
+    }
+
+    /// The size of this region
+    pub const fn size(&self) -> RegionSize {
+        self.size
+    }
+
+    /// The subregion disable mask for this region
+    pub const fn subregion_disable(&self) -> u8 {
+        self.subregion_disable
+    }
+
+    /// The memory type of this region
+    pub const fn mem_attr(&self) -> MemAttr {
+        self.mem_attr
+    }
+
+    /// The access permissions for this region
+    pub const fn access(&self) -> AccessPermissions {
+        self.access
+    }
+
+    /// Is this region Execute Never?
+    pub const fn no_exec(&self) -> bool {
+        self.no_exec
+    }
+
+    /// Check this region can be programmed into the MPU
+    pub const fn validate(&self) -> Result<(), Error> {
+        if (self.base as u64) % self.size.bytes() != 0 {
+            return Err(Error::UnalignedBase(self.base));
+        }
+        if self.subregion_disable != 0 && self.size.bytes() < 256 {
+            return Err(Error::SubregionsUnsupported);
+        }
+        Ok(())
+    }
+}
+
+/// A handle to the PMSAv7 MPU
+pub struct Mpu {
+    _private: (),
+}
+
+impl Mpu {
+    /// Create a handle to the MPU
+    ///
+    /// # Safety
+    ///
+    /// Only one `Mpu` handle may exist at any one time. Configuring the MPU
+    /// changes the attributes of memory that is already in use, so you must
+    /// also ensure that the regions you program do not remove access to
+    /// memory the program is relying on, or change its cacheability without
+    /// appropriate cache maintenance.
+    pub unsafe fn new() -> Mpu {
+        Mpu { _private: () }
+    }
+
+    /// How many data (or unified) regions does this MPU support?
+    pub fn num_regions(&self) -> u8 {
+        Mpuir::read().dregion()
+    }
+
+    /// Read back the configuration of a region
+    ///
+    /// Returns `None` if the region does not exist, is disabled, or holds
+    /// settings that [`Region`] cannot represent.
+    pub fn get_region(&mut self, idx: u8) -> Option<Region> {
+        if idx >= self.num_regions() {
+            return None;
+        }
+        Rgnr::write(Rgnr::new_with_raw_value(0).with_region(idx));
+        crate::asm::isb();
+        let drsr = Drsr::read();
+        if !drsr.en() {
+            return None;
+        }
+        let dracr = Dracr::read();
+        Some(Region {
+            base: Drbar::read().0,
+            size: RegionSize::from_log2(drsr.rsize().value() as u32 + 1)?,
+            subregion_disable: drsr.sd(),
+            mem_attr: MemAttr::decode(&dracr)?,
+            access: AccessPermissions::from_bits(dracr.ap())?,
+            no_exec: dracr.xn(),
+        })
+    }
+
+    /// Program and enable a region
+    pub fn set_region(&mut self, idx: u8, region: &Region) -> Result<(), Error> {
+        if idx >= self.num_regions() {
+            return Err(Error::InvalidRegion(idx));
+        }
+        region.validate()?;
+        let dracr = region.mem_attr.encode(
+            Dracr::new_with_raw_value(0)
+                .with_xn(region.no_exec)
+                .with_ap(u3::new(region.access as u8)),
+        );
+        let drsr = Drsr::new_with_raw_value(0)
+            .with_sd(region.subregion_disable)
+            .with_rsize(u5::new(region.size as u8))
+            .with_en(true);
+        crate::asm::dsb();
+        Rgnr::write(Rgnr::new_with_raw_value(0).with_region(idx));
+        Drbar::write(Drbar(region.base));
+        Dracr::write(dracr);
+        Drsr::write(drsr);
+        crate::asm::dsb();
+        crate::asm::isb();
+        Ok(())
+    }
+
+    /// Disable a region
+    pub fn disable_region(&mut self, idx: u8) -> Result<(), Error> {
+        if idx >= self.num_regions() {
+            return Err(Error::InvalidRegion(idx));
+        }
+        crate::asm::dsb();
+        Rgnr::write(Rgnr::new_with_raw_value(0).with_region(idx));
+        Drsr::write(Drsr::new_with_raw_value(0));
+        crate::asm::dsb();
+        crate::asm::isb();
+        Ok(())
+    }
+
+    /// Is the MPU enabled?
+    pub fn is_enabled(&self) -> bool {
+        Sctlr::read().m()
+    }
+
+    /// Enable the MPU
+    pub fn enable(&mut self) {
+        crate::asm::dsb();
+        Sctlr::modify(|r| r.set_m(true));
+        crate::asm::isb();
+    }
+
+    /// Disable the MPU
+    pub fn disable(&mut self) {
+        crate::asm::dsb();
+        Sctlr::modify(|r| r.set_m(false));
+        crate::asm::isb();
+    }
+
+    /// Enable the default memory map as a background region for privileged
+    /// accesses that do not hit any MPU region
+    pub fn enable_background_region(&mut self) {
+        crate::asm::dsb();
+        Sctlr::modify(|r| r.set_br(true));
+        crate::asm::isb();
+    }
+
+    /// Make privileged accesses that do not hit any MPU region fault
+    pub fn disable_background_region(&mut self) {
+        crate::asm::dsb();
+        Sctlr::modify(|r| r.set_br(false));
+        crate::asm::isb();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn region_size_from_log2() {
+        assert_eq!(RegionSize::from_log2(4), None);
+        assert_eq!(RegionSize::from_log2(5), Some(RegionSize::B32));
+        assert_eq!(RegionSize::from_log2(12), Some(RegionSize::K4));
+        assert_eq!(RegionSize::from_log2(32), Some(RegionSize::G4));
+        assert_eq!(RegionSize::from_log2(33), None);
+        for size in RegionSize::ALL {
+            assert_eq!(RegionSize::from_log2(size.log2()), Some(size));
+        }
+    }
+
+    #[test]
+    fn region_size_encoding() {
+        // RSIZE is log2(bytes) - 1
+        assert_eq!(RegionSize::B32 as u8, 4);
+        assert_eq!(RegionSize::K4.bytes(), 4096);
+        assert_eq!(RegionSize::G4 as u8, 31);
+        assert_eq!(RegionSize::G4.bytes(), 1 << 32);
+    }
+
+    #[test]
+    fn region_size_at_least() {
+        assert_eq!(RegionSize::at_least(0), Some(RegionSize::B32));
+        assert_eq!(RegionSize::at_least(32), Some(RegionSize::B32));
+        assert_eq!(RegionSize::at_least(33), Some(RegionSize::B64));
+        assert_eq!(RegionSize::at_least(4096), Some(RegionSize::K4));
+        assert_eq!(RegionSize::at_least(4097), Some(RegionSize::K8));
+        assert_eq!(RegionSize::at_least(1 << 32), Some(RegionSize::G4));
+        assert_eq!(RegionSize::at_least((1 << 32) + 1), None);
+        assert_eq!(RegionSize::at_least(u64::MAX), None);
+    }
+
+    #[test]
+    fn validate_alignment() {
+        assert_eq!(Region::new(0x2000_0000, RegionSize::M1).validate(), Ok(()));
+        assert_eq!(Region::new(0, RegionSize::G4).validate(), Ok(()));
+        assert_eq!(
+            Region::new(0x2000_1000, RegionSize::K8).validate(),
+            Err(Error::UnalignedBase(0x2000_1000))
+        );
+        assert_eq!(
+            Region::new(0x1000, RegionSize::G4).validate(),
+            Err(Error::UnalignedBase(0x1000))
+        );
+    }
+
+    #[test]
+    fn validate_subregions() {
+        let region = Region::new(0, RegionSize::B256).with_subregion_disable(0x81);
+        assert_eq!(region.validate(), Ok(()));
+        let region = Region::new(0, RegionSize::B128).with_subregion_disable(0x01);
+        assert_eq!(region.validate(), Err(Error::SubregionsUnsupported));
+        let region = Region::new(0, RegionSize::B128);
+        assert_eq!(region.validate(), Ok(()));
+    }
+
+    #[test]
+    fn mem_attr_encoding() {
+        let dracr = MemAttr::Normal {
+            outer: CachePolicy::WriteBackWriteAllocate,
+            inner: CachePolicy::WriteThroughNoWriteAllocate,
+            shareable: true,
+        }
+        .encode(Dracr::new_with_raw_value(0));
+        assert_eq!(dracr.tex().value(), 0b101);
+        assert!(dracr.c());
+        assert!(!dracr.b());
+        assert!(dracr.s());
+
+        let dracr = MemAttr::Device { shareable: false }.encode(Dracr::new_with_raw_value(0));
+        assert_eq!(dracr.tex().value(), 0b010);
+        assert!(!dracr.c());
+        assert!(!dracr.b());
+    }
+
+    #[test]
+    fn mem_attr_round_trip() {
+        let policies = [
+            CachePolicy::NonCacheable,
+            CachePolicy::WriteBackWriteAllocate,
+            CachePolicy::WriteThroughNoWriteAllocate,
+            CachePolicy::WriteBackNoWriteAllocate,
+        ];
+        let round_trip = |attr: MemAttr| {
+            let dracr = attr.encode(Dracr::new_with_raw_value(0));
+            assert_eq!(MemAttr::decode(&dracr), Some(attr));
+        };
+        round_trip(MemAttr::StronglyOrdered);
+        round_trip(MemAttr::Device { shareable: true });
+        round_trip(MemAttr::Device { shareable: false });
+        for outer in policies {
+            for inner in policies {
+                for shareable in [false, true] {
+                    round_trip(MemAttr::Normal {
+                        outer,
+                        inner,
+                        shareable,
+                    });
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn mem_attr_decode_legacy() {
+        // TEX=0b001, C=1, B=1 is Normal, write-back write-allocate
+        let dracr = Dracr::new_with_raw_value(0)
+            .with_tex(u3::new(0b001))
+            .with_c(true)
+            .with_b(true);
+        assert_eq!(
+            MemAttr::decode(&dracr),
+            Some(MemAttr::Normal {
+                outer: CachePolicy::WriteBackWriteAllocate,
+                inner: CachePolicy::WriteBackWriteAllocate,
+                shareable: false,
+            })
+        );
+        // TEX=0b011 is reserved
+        let dracr = Dracr::new_with_raw_value(0).with_tex(u3::new(0b011));
+        assert_eq!(MemAttr::decode(&dracr), None);
+    }
+
+    #[test]
+    fn access_permissions_decode() {
+        for access in [
+            AccessPermissions::NoAccess,
+            AccessPermissions::PrivReadWrite,
+            AccessPermissions::PrivReadWriteUserReadOnly,
+            AccessPermissions::ReadWrite,
+            AccessPermissions::PrivReadOnly,
+            AccessPermissions::ReadOnly,
+        ] {
+            assert_eq!(
+                AccessPermissions::from_bits(u3::new(access as u8)),
+                Some(access)
+            );
+        }
+        assert_eq!(
+            AccessPermissions::from_bits(u3::new(0b111)),
+            Some(AccessPermissions::ReadOnly)
+        );
+        assert_eq!(AccessPermissions::from_bits(u3::new(0b100)), None);
+    }
+}
@@ -0,0 +1,943 @@
+//! Support for the PMSAv8-32 MPU found on Armv8-R processors
+//!
+//! The Cortex-R52 has an EL1 MPU with 16 or 24 regions. Each region is
+//! described by an inclusive base and limit address with 64 byte granularity,
+//! and selects its memory type from one of eight attributes held in MAIR0 and
+//! MAIR1. Regions must not overlap - an access that hits more than one enabled
+//! region will fault.
+//!
+//! There is also an EL2 MPU, which uses the same region model but can only be
+//! programmed from Hyp mode. It controls accesses made from EL2 and, when
+//! `HCR.VM` is set, also constrains accesses made from EL1 and EL0.
+
+use arbitrary_int::{u2, u26, u3};
+
+use crate::register::{
+    cpsr::ProcessorMode, Cpsr, Hmair0, Hmair1, Hmpuir, Hprbar, Hprenr, Hprlar, Hprselr, Hsctlr,
+    Mair0, Mair1, Mpuir, Prbar, Prlar, Prselr, Sctlr,
+};
+
+/// The ways in which programming the MPU can fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// This MPU does not have a region with the given number
+    InvalidRegion(u8),
+    /// The base address is not a multiple of 64
+    UnalignedBase(u32),
+    /// The limit address is not one less than a multiple of 64
+    UnalignedLimit(u32),
+    /// The limit address is below the base address
+    EmptyRegion,
+    /// The memory attribute index is larger than 7
+    InvalidAttrIndex(u8),
+    /// More regions were given than this MPU supports
+    TooManyRegions,
+    /// More than eight memory attributes were given
+    TooManyAttributes,
+}
+
+/// Shareability of an MPU region
+///
+/// The discriminant is the value programmed into the SH field of PRBAR.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum Shareability {
+    /// Non-shareable
+    NonShareable = 0b00,
+    /// Outer Shareable
+    OuterShareable = 0b10,
+    /// Inner Shareable
+    InnerShareable = 0b11,
+}
+
+impl Shareability {
+    /// Decode the SH field of PRBAR
+    const fn from_bits(bits: u2) -> Option<Shareability> {
+        match bits.value() {
+            0b00 => Some(Shareability::NonShareable),
+            0b10 => Some(Shareability::OuterShareable),
+            0b11 => Some(Shareability::InnerShareable),
+            _ => None,
+        }
+    }
+}
+
+/// Access permissions for an MPU region
+///
+/// The discriminant is the value programmed into the AP field of PRBAR.
+/// Privileged means EL1 for the EL1 MPU, or EL2 for the EL2 MPU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum AccessPermissions {
+    /// Read/write when privileged, no access from EL0
+    PrivReadWrite = 0b00,
+    /// Read/write at any exception level
+    ReadWrite = 0b01,
+    /// Read-only when privileged, no access from EL0
+    PrivReadOnly = 0b10,
+    /// Read-only at any exception level
+    ReadOnly = 0b11,
+}
+
+impl AccessPermissions {
+    /// Decode the AP field of PRBAR
+    const fn from_bits(bits: u2) -> AccessPermissions {
+        match bits.value() {
+            0b00 => AccessPermissions::PrivReadWrite,
+            0b01 => AccessPermissions::ReadWrite,
+            0b10 => AccessPermissions::PrivReadOnly,
+            _ => AccessPermissions::ReadOnly,
+        }
+    }
+}
+
+/// The kind of Device memory
+///
+/// The discriminant is the `dd` field of a `0b0000_dd00` Device memory
+/// attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum DeviceMemory {
+    /// Non-Gathering, Non-Reordering, No Early write acknowledgement
+    NGnRnE = 0b00,
+    /// Non-Gathering, Non-Reordering, Early write acknowledgement
+    NGnRE = 0b01,
+    /// Non-Gathering, Reordering, Early write acknowledgement
+    NGRE = 0b10,
+    /// Gathering, Reordering, Early write acknowledgement
+    GRE = 0b11,
+}
+
+/// The cacheability of one level (inner or outer) of Normal memory
+///
+/// Cacheable memory is always marked as Non-transient.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Cacheability {
+    /// Non-cacheable
+    NonCacheable,
+    /// Write-Through cacheable
+    WriteThrough {
+        /// Allocate cache lines on a read miss
+        read_allocate: bool,
+        /// Allocate cache lines on a write miss
+        write_allocate: bool,
+    },
+    /// Write-Back cacheable
+    WriteBack {
+        /// Allocate cache lines on a read miss
+        read_allocate: bool,
+        /// Allocate cache lines on a write miss
+        write_allocate: bool,
+    },
+}
+
+impl Cacheability {
+    /// Encode as a four-bit MAIR field
+    const fn encode(self) -> u8 {
+        match self {
+            Cacheability::NonCacheable => 0b0100,
+            Cacheability::WriteThrough {
+                read_allocate,
+                write_allocate,
+            } => 0b1000 | ((read_allocate as u8) << 1) | (write_allocate as u8),
+            Cacheability::WriteBack {
+                read_allocate,
+                write_allocate,
+            } => 0b1100 | ((read_allocate as u8) << 1) | (write_allocate as u8),
+        }
+    }
+
+    /// Decode a four-bit MAIR field
+    ///
+    /// Transient encodings are not supported.
+    const fn decode(bits: u8) -> Option<Cacheability> {
+        let read_allocate = (bits & 0b10) != 0;
+        let write_allocate = (bits & 0b01) != 0;
+        match bits & 0b1100 {
+            0b0100 if bits == 0b0100 => Some(Cacheability::NonCacheable),
+            0b1000 => Some(Cacheability::WriteThrough {
+                read_allocate,
+                write_allocate,
+            }),
+            0b1100 => Some(Cacheability::WriteBack {
+                read_allocate,
+                write_allocate,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// A memory attribute, as held in one of the eight fields of MAIR0/MAIR1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MemAttr {
+    /// Device memory
+    Device(DeviceMemory),
+    /// Normal memory
+    Normal {
+        /// The outer cacheability
+        outer: Cacheability,
+        /// The inner cacheability
+        inner: Cacheability,
+    },
+}
+
+impl MemAttr {
+    /// Normal memory, Write-Back cacheable with read and write allocation
+    pub const NORMAL_WRITE_BACK: MemAttr = MemAttr::Normal {
+        outer: Cacheability::WriteBack {
+            read_allocate: true,
+            write_allocate: true,
+        },
+        inner: Cacheability::WriteBack {
+            read_allocate: true,
+            write_allocate: true,
+        },
+    };
+
+    /// Normal memory, Write-Through cacheable with read allocation
+    pub const NORMAL_WRITE_THROUGH: MemAttr = MemAttr::Normal {
+        outer: Cacheability::WriteThrough {
+            read_allocate: true,
+            write_allocate: false,
+        },
+        inner: Cacheability::WriteThrough {
+            read_allocate: true,
+            write_allocate: false,
+        },
+    };
+
+    /// Normal memory, Non-cacheable
+    pub const NORMAL_NON_CACHEABLE: MemAttr = MemAttr::Normal {
+        outer: Cacheability::NonCacheable,
+        inner: Cacheability::NonCacheable,
+    };
+
+    /// Encode as an eight-bit MAIR field
+    pub const fn encode(self) -> u8 {
+        match self {
+            MemAttr::Device(kind) => (kind as u8) << 2,
+            MemAttr::Normal { outer, inner } => (outer.encode() << 4) | inner.encode(),
+        }
+    }
+
+    /// Decode an eight-bit MAIR field
+    ///
+    /// Returns `None` for reserved or Transient encodings.
+    pub const fn decode(bits: u8) -> Option<MemAttr> {
+        if (bits & 0xF0) == 0 {
+            return match bits {
+                0b0000_0000 => Some(MemAttr::Device(DeviceMemory::NGnRnE)),
+                0b0000_0100 => Some(MemAttr::Device(DeviceMemory::NGnRE)),
+                0b0000_1000 => Some(MemAttr::Device(DeviceMemory::NGRE)),
+                0b0000_1100 => Some(MemAttr::Device(DeviceMemory::GRE)),
+                _ => None,
+            };
+        }
+        let Some(outer) = Cacheability::decode(bits >> 4) else {
+            return None;
+        };
+        let Some(inner) = Cacheability::decode(bits & 0x0F) else {
+            return None;
+        };
+        Some(MemAttr::Normal { outer, inner })
+    }
+}
+
+/// Describes a PMSAv8-32 MPU region
+///
+/// Build one with [`Region::new`] and the `with_` methods. The defaults are
+/// Non-shareable, read/write only when privileged, never executable, and using
+/// memory attribute 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Region {
+    base: u32,
+    limit: u32,
+    shareability: Shareability,
+    access: AccessPermissions,
+    no_exec: bool,
+    mair_index: u8,
+}
+
+impl Region {
+    /// Create a new region description
+    ///
+    /// The `base` is the address of the first byte in the region and must be a
+    /// multiple of 64. The `limit` is the address of the last byte in the
+    /// region, so must be one less than a multiple of 64.
+    pub const fn new(base: u32, limit: u32) -> Region {
+        Region {
+            base,
+            limit,
+            shareability: Shareability::NonShareable,
+            access: AccessPermissions::PrivReadWrite,
+            no_exec: true,
+            mair_index: 0,
+        }
+    }
+
+    /// Set the shareability of this region
+    pub const fn with_shareability(self, shareability: Shareability) -> Region {
+        Region {
+            shareability,
+            ..self
+        }
+    }
+
+    /// Set the access permissions for this region
+    pub const fn with_access(self, access: AccessPermissions) -> Region {
+        Region { access, ..self }
+    }
+
+    /// Set whether instructions can be fetched from this region
+    pub const fn with_no_exec(self, no_exec: bool) -> Region {
+        Region { no_exec, ..self }
+    }
+
+    /// Select which memory attribute (0 to 7) from MAIR0/MAIR1 this region uses
+    pub const fn with_mair_index(self, mair_index: u8) -> Region {
+        Region { mair_index, ..self }
+    }
+
+    /// The address of the first byte in this region
+    pub const fn base(&self) -> u32 {
+        self.base
+    }
+
+    /// The address of the last byte in this region
+    pub const fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    /// The shareability of this region
+    pub const fn shareability(&self) -> Shareability {
+        self.shareability
+    }
+
+    /// The access permissions for this region
+    pub const fn access(&self) -> AccessPermissions {
+        self.access
+    }
+
+    /// Is this region Execute Never?
+    pub const fn no_exec(&self) -> bool {
+        self.no_exec
+    }
+
+    /// Which memory attribute from MAIR0/MAIR1 this region uses
+    pub const fn mair_index(&self) -> u8 {
+        self.mair_index
+    }
+
+    /// Check this region can be programmed into the MPU
+    pub const fn validate(&self) -> Result<(), Error> {
+        if (self.base & 0x3F) != 0 {
+            return Err(Error::UnalignedBase(self.base));
+        }
+        if (self.limit & 0x3F) != 0x3F {
+            return Err(Error::UnalignedLimit(self.limit));
+        }
+        if self.limit < self.base {
+            return Err(Error::EmptyRegion);
+        }
+        if self.mair_index > 7 {
+            return Err(Error::InvalidAttrIndex(self.mair_index));
+        }
+        Ok(())
+    }
+
+    /// Get the PRBAR value for this region
+    const fn prbar(&self) -> Prbar {
+        Prbar::new_with_raw_value(0)
+            .with_base(u26::new(self.base >> 6))
+            .with_sh(u2::new(self.shareability as u8))
+            .with_ap(u2::new(self.access as u8))
+            .with_xn(self.no_exec)
+    }
+
+    /// Get the PRLAR value for this region, with the region enabled
+    const fn prlar(&self) -> Prlar {
+        Prlar::new_with_raw_value(0)
+            .with_limit(u26::new(self.limit >> 6))
+            .with_attrindx(u3::new(self.mair_index))
+            .with_en(true)
+    }
+
+    /// Get the HPRBAR value for this region
+    ///
+    /// HPRBAR has the same layout as PRBAR.
+    const fn hprbar(&self) -> Hprbar {
+        Hprbar::new_with_raw_value(self.prbar().raw_value())
+    }
+
+    /// Get the HPRLAR value for this region, with the region enabled
+    ///
+    /// HPRLAR has the same layout as PRLAR.
+    const fn hprlar(&self) -> Hprlar {
+        Hprlar::new_with_raw_value(self.prlar().raw_value())
+    }
+
+    /// Decode the PRBAR and PRLAR values for a region
+    const fn from_registers(prbar: &Prbar, prlar: &Prlar) -> Option<Region> {
+        let Some(shareability) = Shareability::from_bits(prbar.sh()) else {
+            return None;
+        };
+        Some(Region {
+            base: prbar.base().value() << 6,
+            limit: (prlar.limit().value() << 6) | 0x3F,
+            shareability,
+            access: AccessPermissions::from_bits(prbar.ap()),
+            no_exec: prbar.xn(),
+            mair_index: prlar.attrindx().value(),
+        })
+    }
+}
+
+/// A complete MPU configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Config<'a> {
+    /// The regions to program, starting at region 0
+    ///
+    /// Any remaining regions are disabled.
+    pub regions: &'a [Region],
+    /// The memory attributes to program into MAIR0 and MAIR1, starting with
+    /// attribute 0
+    ///
+    /// Any remaining attributes are set to Device-nGnRnE.
+    pub memory_attributes: &'a [MemAttr],
+    /// Whether privileged accesses that miss every region should use the
+    /// default memory map (rather than fault)
+    pub background_config: bool,
+}
+
+impl Config<'_> {
+    /// Check this configuration can be programmed into an MPU with the given
+    /// number of regions
+    fn validate(&self, num_regions: u8) -> Result<(), Error> {
+        if self.regions.len() > num_regions as usize {
+            return Err(Error::TooManyRegions);
+        }
+        if self.memory_attributes.len() > 8 {
+            return Err(Error::TooManyAttributes);
+        }
+        for region in self.regions {
+            region.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Pack the memory attributes into MAIR0 and MAIR1 values
+    fn mair_values(&self) -> (u32, u32) {
+        let mut bytes = [0u8; 8];
+        for (byte, attr) in bytes.iter_mut().zip(self.memory_attributes) {
+            *byte = attr.encode();
+        }
+        (
+            u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+        )
+    }
+}
+
+/// A handle to the EL1 MPU
+pub struct El1Mpu {
+    _private: (),
+}
+
+impl El1Mpu {
+    /// Create a handle to the EL1 MPU
+    ///
+    /// # Safety
+    ///
+    /// Only one `El1Mpu` handle may exist at any one time. Configuring the MPU
+    /// changes the attributes of memory that is already in use, so you must
+    /// also ensure that the regions you program do not remove access to
+    /// memory the program is relying on, or change its cacheability without
+    /// appropriate cache maintenance.
+    pub unsafe fn new() -> El1Mpu {
+        El1Mpu { _private: () }
+    }
+
+    /// How many regions does this MPU support?
+    pub fn num_regions(&self) -> u8 {
+        Mpuir::read().dregion()
+    }
+
+    /// Read back the configuration of a region
+    ///
+    /// Returns `None` if the region does not exist, is disabled, or holds
+    /// settings that [`Region`] cannot represent.
+    pub fn get_region(&mut self, idx: u8) -> Option<Region> {
+        if idx >= self.num_regions() {
+            return None;
+        }
+        Prselr::write(Prselr::new_with_raw_value(0).with_region(idx));
+        crate::asm::isb();
+        let prlar = Prlar::read();
+        if !prlar.en() {
+            return None;
+        }
+        Region::from_registers(&Prbar::read(), &prlar)
+    }
+
+    /// Program and enable a region
+    pub fn set_region(&mut self, idx: u8, region: &Region) -> Result<(), Error> {
+        if idx >= self.num_regions() {
+            return Err(Error::InvalidRegion(idx));
+        }
+        region.validate()?;
+        crate::asm::dsb();
+        Prselr::write(Prselr::new_with_raw_value(0).with_region(idx));
+        crate::asm::isb();
+        Prbar::write(region.prbar());
+        Prlar::write(region.prlar());
+        crate::asm::dsb();
+        crate::asm::isb();
+        Ok(())
+    }
+
+    /// Disable a region
+    pub fn disable_region(&mut self, idx: u8) -> Result<(), Error> {
+        if idx >= self.num_regions() {
+            return Err(Error::InvalidRegion(idx));
+        }
+        crate::asm::dsb();
+        Prselr::write(Prselr::new_with_raw_value(0).with_region(idx));
+        crate::asm::isb();
+        Prlar::write(Prlar::new_with_raw_value(0));
+        crate::asm::dsb();
+        crate::asm::isb();
+        Ok(())
+    }
+
+    /// Program the memory attributes in MAIR0 and MAIR1
+    ///
+    /// Any attributes not given are set to Device-nGnRnE.
+    pub fn set_attributes(&mut self, memory_attributes: &[MemAttr]) -> Result<(), Error> {
+        if memory_attributes.len() > 8 {
+            return Err(Error::TooManyAttributes);
+        }
+        let config = Config {
+            regions: &[],
+            memory_attributes,
+            background_config: false,
+        };
+        let (mair0, mair1) = config.mair_values();
+        crate::asm::dsb();
+        Mair0::write(Mair0::new_with_raw_value(mair0));
+        Mair1::write(Mair1::new_with_raw_value(mair1));
+        crate::asm::dsb();
+        crate::asm::isb();
+        Ok(())
+    }
+
+    /// Replace the entire MPU configuration, and enable the MPU
+    ///
+    /// The configuration is checked before anything is changed. It is then
+    /// programmed with interrupts masked and the MPU disabled, so no code runs
+    /// while the region table is partially written.
+    pub fn configure(&mut self, config: &Config) -> Result<(), Error> {
+        let num_regions = self.num_regions();
+        config.validate(num_regions)?;
+        let (mair0, mair1) = config.mair_values();
+        crate::interrupt::free(|_cs| {
+            self.disable();
+            Mair0::write(Mair0::new_with_raw_value(mair0));
+            Mair1::write(Mair1::new_with_raw_value(mair1));
+            for idx in 0..num_regions {
+                Prselr::write(Prselr::new_with_raw_value(0).with_region(idx));
+                crate::asm::isb();
+                match config.regions.get(idx as usize) {
+                    Some(region) => {
+                        Prbar::write(region.prbar());
+                        Prlar::write(region.prlar());
+                    }
+                    None => {
+                        Prlar::write(Prlar::new_with_raw_value(0));
+                    }
+                }
+            }
+            crate::asm::dsb();
+            Sctlr::modify(|r| {
+                r.set_br(config.background_config);
+                r.set_m(true);
+            });
+            crate::asm::isb();
+        });
+        Ok(())
+    }
+
+    /// Is the MPU enabled?
+    pub fn is_enabled(&self) -> bool {
+        Sctlr::read().m()
+    }
+
+    /// Enable the MPU
+    pub fn enable(&mut self) {
+        crate::asm::dsb();
+        Sctlr::modify(|r| r.set_m(true));
+        crate::asm::isb();
+    }
+
+    /// Disable the MPU
+    pub fn disable(&mut self) {
+        crate::asm::dsb();
+        Sctlr::modify(|r| r.set_m(false));
+        crate::asm::isb();
+    }
+
+    /// Enable the default memory map as a background region for EL1 accesses
+    /// that do not hit any MPU region
+    pub fn enable_background_region(&mut self) {
+        crate::asm::dsb();
+        Sctlr::modify(|r| r.set_br(true));
+        crate::asm::isb();
+    }
+
+    /// Make EL1 accesses that do not hit any MPU region fault
+    pub fn disable_background_region(&mut self) {
+        crate::asm::dsb();
+        Sctlr::modify(|r| r.set_br(false));
+        crate::asm::isb();
+    }
+}
+
+/// A handle to the EL2 MPU
+pub struct El2Mpu {
+    _private: (),
+}
+
+impl El2Mpu {
+    /// Create a handle to the EL2 MPU
+    ///
+    /// Returns `None` if the processor is not in Hyp mode, because the EL2 MPU
+    /// registers cannot be accessed from EL1.
+    ///
+    /// # Safety
+    ///
+    /// Only one `El2Mpu` handle may exist at any one time, and it must not be
+    /// used after leaving Hyp mode. Configuring the MPU changes the attributes
+    /// of memory that is already in use, so you must also ensure that the
+    /// regions you program do not remove access to memory the program is
+    /// relying on, or change its cacheability without appropriate cache
+    /// maintenance.
+    pub unsafe fn new() -> Option<El2Mpu> {
+        if matches!(Cpsr::read().mode(), Ok(ProcessorMode::Hyp)) {
+            Some(El2Mpu { _private: () })
+        } else {
+            None
+        }
+    }
+
+    /// How many regions does this MPU support?
+    pub fn num_regions(&self) -> u8 {
+        Hmpuir::read().region()
+    }
+
+    /// Read back the configuration of a region
+    ///
+    /// Returns `None` if the region does not exist, is disabled, or holds
+    /// settings that [`Region`] cannot represent.
+    pub fn get_region(&mut self, idx: u8) -> Option<Region> {
+        if idx >= self.num_regions() {
+            return None;
+        }
+        Hprselr::write(Hprselr::new_with_raw_value(0).with_region(idx));
+        crate::asm::isb();
+        let hprlar = Hprlar::read();
+        if !hprlar.en() {
+            return None;
+        }
+        Region::from_registers(
+            &Prbar::new_with_raw_value(Hprbar::read().raw_value()),
+            &Prlar::new_with_raw_value(hprlar.raw_value()),
+        )
+    }
+
+    /// Program and enable a region
+    pub fn set_region(&mut self, idx: u8, region: &Region) -> Result<(), Error> {
+        if idx >= self.num_regions() {
+            return Err(Error::InvalidRegion(idx));
+        }
+        region.validate()?;
+        crate::asm::dsb();
+        Hprselr::write(Hprselr::new_with_raw_value(0).with_region(idx));
+        crate::asm::isb();
+        Hprbar::write(region.hprbar());
+        Hprlar::write(region.hprlar());
+        crate::asm::dsb();
+        crate::asm::isb();
+        Ok(())
+    }
+
+    /// Disable a region
+    pub fn disable_region(&mut self, idx: u8) -> Result<(), Error> {
+        if idx >= self.num_regions() {
+            return Err(Error::InvalidRegion(idx));
+        }
+        crate::asm::dsb();
+        Hprselr::write(Hprselr::new_with_raw_value(0).with_region(idx));
+        crate::asm::isb();
+        Hprlar::write(Hprlar::new_with_raw_value(0));
+        crate::asm::dsb();
+        crate::asm::isb();
+        Ok(())
+    }
+
+    /// Get the enable bits for the first 32 regions
+    ///
+    /// Bit N is set if region N is enabled.
+    pub fn enabled_regions(&self) -> u32 {
+        Hprenr::read().0
+    }
+
+    /// Set the enable bits for the first 32 regions in a single write
+    ///
+    /// Bit N enables region N. Regions must have been programmed with
+    /// [`El2Mpu::set_region`] before they are enabled this way.
+    pub fn set_enabled_regions(&mut self, mask: u32) {
+        crate::asm::dsb();
+        Hprenr::write(Hprenr(mask));
+        crate::asm::dsb();
+        crate::asm::isb();
+    }
+
+    /// Program the memory attributes in HMAIR0 and HMAIR1
+    ///
+    /// Any attributes not given are set to Device-nGnRnE.
+    pub fn set_attributes(&mut self, memory_attributes: &[MemAttr]) -> Result<(), Error> {
+        if memory_attributes.len() > 8 {
+            return Err(Error::TooManyAttributes);
+        }
+        let config = Config {
+            regions: &[],
+            memory_attributes,
+            background_config: false,
+        };
+        let (hmair0, hmair1) = config.mair_values();
+        crate::asm::dsb();
+        Hmair0::write(Hmair0::new_with_raw_value(hmair0));
+        Hmair1::write(Hmair1::new_with_raw_value(hmair1));
+        crate::asm::dsb();
+        crate::asm::isb();
+        Ok(())
+    }
+
+    /// Replace the entire MPU configuration, and enable the MPU
+    ///
+    /// The configuration is checked before anything is changed. It is then
+    /// programmed with interrupts masked and the MPU disabled, so no code runs
+    /// while the region table is partially written.
+    pub fn configure(&mut self, config: &Config) -> Result<(), Error> {
+        let num_regions = self.num_regions();
+        config.validate(num_regions)?;
+        let (hmair0, hmair1) = config.mair_values();
+        crate::interrupt::free(|_cs| {
+            self.disable();
+            Hmair0::write(Hmair0::new_with_raw_value(hmair0));
+            Hmair1::write(Hmair1::new_with_raw_value(hmair1));
+            for idx in 0..num_regions {
+                Hprselr::write(Hprselr::new_with_raw_value(0).with_region(idx));
+                crate::asm::isb();
+                match config.regions.get(idx as usize) {
+                    Some(region) => {
+                        Hprbar::write(region.hprbar());
+                        Hprlar::write(region.hprlar());
+                    }
+                    None => {
+                        Hprlar::write(Hprlar::new_with_raw_value(0));
+                    }
+                }
+            }
+            crate::asm::dsb();
+            Hsctlr::modify(|r| {
+                r.set_br(config.background_config);
+                r.set_m(true);
+            });
+            crate::asm::isb();
+        });
+        Ok(())
+    }
+
+    /// Is the MPU enabled?
+    pub fn is_enabled(&self) -> bool {
+        Hsctlr::read().m()
+    }
+
+    /// Enable the MPU
+    pub fn enable(&mut self) {
+        crate::asm::dsb();
+        Hsctlr::modify(|r| r.set_m(true));
+        crate::asm::isb();
+    }
+
+    /// Disable the MPU
+    pub fn disable(&mut self) {
+        crate::asm::dsb();
+        Hsctlr::modify(|r| r.set_m(false));
+        crate::asm::isb();
+    }
+
+    /// Enable the default memory map as a background region for EL2 accesses
+    /// that do not hit any MPU region
+    pub fn enable_background_region(&mut self) {
+        crate::asm::dsb();
+        Hsctlr::modify(|r| r.set_br(true));
+        crate::asm::isb();
+    }
+
+    /// Make EL2 accesses that do not hit any MPU region fault
+    pub fn disable_background_region(&mut self) {
+        crate::asm::dsb();
+        Hsctlr::modify(|r| r.set_br(false));
+        crate::asm::isb();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mem_attr_encoding() {
+        assert_eq!(MemAttr::Device(DeviceMemory::NGnRnE).encode(), 0x00);
+        assert_eq!(MemAttr::Device(DeviceMemory::NGnRE).encode(), 0x04);
+        assert_eq!(MemAttr::Device(DeviceMemory::GRE).encode(), 0x0C);
+        assert_eq!(MemAttr::NORMAL_WRITE_BACK.encode(), 0xFF);
+        assert_eq!(MemAttr::NORMAL_WRITE_THROUGH.encode(), 0xAA);
+        assert_eq!(MemAttr::NORMAL_NON_CACHEABLE.encode(), 0x44);
+    }
+
+    #[test]
+    fn mem_attr_decode() {
+        for bits in 0..=255u8 {
+            if let Some(attr) = MemAttr::decode(bits) {
+                assert_eq!(attr.encode(), bits, "0x{:02x}", bits);
+            }
+        }
+        assert_eq!(MemAttr::decode(0xFF), Some(MemAttr::NORMAL_WRITE_BACK));
+        // Device memory with the low bits set is reserved
+        assert_eq!(MemAttr::decode(0x01), None);
+        assert_eq!(
+            MemAttr::decode(0x4F),
+            Some(MemAttr::Normal {
+                outer: Cacheability::NonCacheable,
+                inner: Cacheability::WriteBack {
+                    read_allocate: true,
+                    write_allocate: true,
+                },
+            })
+        );
+        // Transient encodings are not supported
+        assert_eq!(MemAttr::decode(0x1F), None);
+        assert_eq!(MemAttr::decode(0xF3), None);
+        // Non-cacheable with allocation hints is reserved
+        assert_eq!(MemAttr::decode(0x45), None);
+    }
+
+    #[test]
+    fn validate_region() {
+        assert_eq!(Region::new(0x1000, 0x1FFF).validate(), Ok(()));
+        assert_eq!(Region::new(0, 0x3F).validate(), Ok(()));
+        assert_eq!(Region::new(0, u32::MAX).validate(), Ok(()));
+        assert_eq!(
+            Region::new(0x1020, 0x1FFF).validate(),
+            Err(Error::UnalignedBase(0x1020))
+        );
+        assert_eq!(
+            Region::new(0x1000, 0x2000).validate(),
+            Err(Error::UnalignedLimit(0x2000))
+        );
+        assert_eq!(
+            Region::new(0x2000, 0x1FFF).validate(),
+            Err(Error::EmptyRegion)
+        );
+        assert_eq!(
+            Region::new(0x1000, 0x1FFF).with_mair_index(8).validate(),
+            Err(Error::InvalidAttrIndex(8))
+        );
+    }
+
+    #[test]
+    fn region_register_round_trip() {
+        let region = Region::new(0x2000_0040, 0x2000_FFFF)
+            .with_shareability(Shareability::InnerShareable)
+            .with_access(AccessPermissions::ReadOnly)
+            .with_no_exec(false)
+            .with_mair_index(5);
+        let prbar = region.prbar();
+        let prlar = region.prlar();
+        // BASE[31:6] | SH[4:3] | AP[2:1] | XN[0]
+        assert_eq!(prbar.raw_value(), 0x2000_0040 | (0b11 << 3) | (0b11 << 1));
+        // LIMIT[31:6] | AttrIndx[3:1] | EN[0]
+        assert_eq!(prlar.raw_value(), 0x2000_FFC0 | (5 << 1) | 1);
+        assert_eq!(Region::from_registers(&prbar, &prlar), Some(region));
+        assert_eq!(region.hprbar().raw_value(), prbar.raw_value());
+        assert_eq!(region.hprlar().raw_value(), prlar.raw_value());
+
+        let region = Region::new(0, 0x3F);
+        assert_eq!(region.prbar().raw_value(), 1);
+        assert_eq!(
+            Region::from_registers(&region.prbar(), &region.prlar()),
+            Some(region)
+        );
+
+        // SH = 0b01 is reserved
+        let prbar = Prbar::new_with_raw_value(0b01 << 3);
+        assert_eq!(Region::from_registers(&prbar, &region.prlar()), None);
+    }
+
+    #[test]
+    fn validate_config() {
+        let regions = [
+            Region::new(0x0000_0000, 0x0000_FFFF),
+            Region::new(0x1000_0000, 0x1000_FFFF),
+        ];
+        let config = Config {
+            regions: &regions,
+            memory_attributes: &[MemAttr::NORMAL_WRITE_BACK],
+            background_config: false,
+        };
+        assert_eq!(config.validate(16), Ok(()));
+        assert_eq!(config.validate(2), Ok(()));
+        assert_eq!(config.validate(1), Err(Error::TooManyRegions));
+
+        let config = Config {
+            memory_attributes: &[MemAttr::NORMAL_NON_CACHEABLE; 9],
+            ..config
+        };
+        assert_eq!(config.validate(16), Err(Error::TooManyAttributes));
+
+        let bad = [Region::new(0x0000_0000, 0x0000_FFFF).with_mair_index(9)];
+        let config = Config {
+            regions: &bad,
+            memory_attributes: &[],
+            background_config: false,
+        };
+        assert_eq!(config.validate(16), Err(Error::InvalidAttrIndex(9)));
+    }
+
+    #[test]
+    fn mair_packing() {
+        let config = Config {
+            regions: &[],
+            memory_attributes: &[
+                MemAttr::NORMAL_WRITE_BACK,
+                MemAttr::Device(DeviceMemory::NGnRE),
+                MemAttr::NORMAL_NON_CACHEABLE,
+                MemAttr::NORMAL_NON_CACHEABLE,
+                MemAttr::NORMAL_WRITE_THROUGH,
+            ],
+            background_config: false,
+        };
+        assert_eq!(config.mair_values(), (0x4444_04FF, 0x0000_00AA));
+    }
+}
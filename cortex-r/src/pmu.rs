@@ -0,0 +1,29 @@
+//! Support for the Performance Monitors Unit (PMU)
+//!
+//! Only the cycle counter is supported so far. It is 32 bits wide, so it wraps
+//! every 2^32 cycles - use `wrapping_sub` to measure anything shorter than that.
+
+use crate::register::{Pmccntr, Pmcntenset, Pmcr};
+
+/// Reset the cycle counter to zero, and start it counting every processor
+/// clock cycle
+pub fn enable_cycle_counter() {
+    Pmcr::modify(|r| {
+        r.set_e(true);
+        r.set_c(true);
+        r.set_d(false);
+    });
+    Pmcntenset::write(Pmcntenset::new_with_raw_value(0).with_c(true));
+    crate::asm::isb();
+}
+
+/// Is the cycle counter counting?
+pub fn cycle_counter_enabled() -> bool {
+    Pmcr::read().e() && Pmcntenset::read().c()
+}
+
+/// Read the cycle counter
+#[inline]
+pub fn cycle_count() -> u32 {
+    Pmccntr::read().0
+}
@@ -0,0 +1,120 @@
+//! Probing memory which may not exist
+//!
+//! Reading an address with nothing behind it causes a Data Abort. The
+//! [`probe_read`] function marks the load it is about to do, so that the Data
+//! Abort handler can call [`fixup_abort`] to skip over it and return to the
+//! probe, which then reports the [`Fault`] instead.
+//!
+//! The default Data Abort handler in `cortex-r-rt` does this for you, and so
+//! do handlers marked with its `#[exception(DataAbort)]` attribute. If you
+//! write `_abort_handler` yourself, call [`fixup_abort`] first.
+
+use core::sync::atomic::{compiler_fence, AtomicUsize, Ordering};
+
+use crate::abort::Fault;
+
+/// The address of the load instruction of the probe in progress (or zero)
+static PROBE_LOAD: AtomicUsize = AtomicUsize::new(0);
+
+/// Where to resume if the probe in progress faults (or zero)
+static PROBE_RESUME: AtomicUsize = AtomicUsize::new(0);
+
+mod sealed {
+    pub trait Sealed {}
+    impl Sealed for u8 {}
+    impl Sealed for u16 {}
+    impl Sealed for u32 {}
+}
+
+/// A value which can be read by [`probe_read`]
+///
+/// This is implemented for `u8`, `u16` and `u32`, which are each read with a
+/// single load instruction.
+pub trait Probe: Copy + sealed::Sealed {
+    /// Perform a single load from the given address
+    ///
+    /// Returns `None` if the load faulted.
+    #[doc(hidden)]
+    unsafe fn load(address: usize) -> Option<Self>;
+}
+
+macro_rules! impl_probe {
+    ($t:ty, $instr:literal) => {
+        impl Probe for $t {
+            #[inline(always)]
+            unsafe fn load(address: usize) -> Option<Self> {
+                let value: u32;
+                let faulted: u32;
+                // Safety: The caller has promised the load is OK to do. If it
+                // faults, `fixup_abort` will resume at label 3.
+                unsafe {
+                    core::arch::asm!(
+                        "adr     {tmp}, 2f",
+                        "str     {tmp}, [{load}]",
+                        "adr     {tmp}, 3f",
+                        "str     {tmp}, [{resume}]",
+                        "mov     {faulted}, #1",
+                        "2:",
+                        concat!($instr, "    {value}, [{address}]"),
+                        "mov     {faulted}, #0",
+                        "3:",
+                        "mov     {tmp}, #0",
+                        "str     {tmp}, [{load}]",
+                        "str     {tmp}, [{resume}]",
+                        tmp = out(reg) _,
+                        load = in(reg) PROBE_LOAD.as_ptr(),
+                        resume = in(reg) PROBE_RESUME.as_ptr(),
+                        address = in(reg) address,
+                        value = out(reg) value,
+                        faulted = out(reg) faulted,
+                        options(nostack),
+                    );
+                }
+                if faulted == 0 {
+                    Some(value as $t)
+                } else {
+                    None
+                }
+            }
+        }
+    };
+}
+
+impl_probe!(u8, "ldrb");
+impl_probe!(u16, "ldrh");
+impl_probe!(u32, "ldr ");
+
+/// Read from an address, reporting a Data Abort instead of taking it
+///
+/// This needs a Data Abort handler which calls [`fixup_abort`], like the
+/// default one in `cortex-r-rt`. It does not catch asynchronous (imprecise)
+/// aborts, as those are reported some time after the load.
+///
+/// # Safety
+///
+/// Reading the address must not have side-effects you aren't prepared for
+/// (e.g. popping a value from a peripheral's FIFO).
+pub unsafe fn probe_read<T: Probe>(address: usize) -> Result<T, Fault> {
+    crate::interrupt::free(|_cs| {
+        compiler_fence(Ordering::SeqCst);
+        // Safety: passed on to our caller
+        let result = unsafe { T::load(address) };
+        compiler_fence(Ordering::SeqCst);
+        result.ok_or_else(Fault::read_data_abort)
+    })
+}
+
+/// Recover from a Data Abort caused by [`probe_read`]
+///
+/// Call this from your Data Abort handler, passing the address of the
+/// instruction which faulted (i.e. the exception return address, minus eight).
+/// If the abort came from a probe, you get back the address to return to
+/// instead. Otherwise you get `None`, and the abort is yours to deal with.
+pub fn fixup_abort(faulting_instruction: usize) -> Option<usize> {
+    let load = PROBE_LOAD.load(Ordering::Relaxed);
+    if load != 0 && load == faulting_instruction {
+        Some(PROBE_RESUME.load(Ordering::Relaxed))
+    } else {
+        None
+    }
+}
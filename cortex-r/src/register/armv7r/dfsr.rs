@@ -0,0 +1,80 @@
+//! Code for managing the *Data Fault Status Register*
+
+use arbitrary_int::{u4, u5};
+
+/// The *Data Fault Status Register* (DFSR)
+///
+/// Holds the cause of the most recent Data Abort.
+#[bitbybit::bitfield(u32)]
+pub struct Dfsr {
+    /// External abort type (SLVERR when set, DECERR when clear, on the
+    /// Cortex-R5)
+    #[bits(12..=12, rw)]
+    ext: bool,
+    /// Write not Read
+    #[bits(11..=11, rw)]
+    wnr: bool,
+    /// Fault Status bit 4
+    #[bits(10..=10, rw)]
+    fs_hi: bool,
+    /// Fault Status bits 3 to 0
+    #[bits(0..=3, rw)]
+    fs_lo: u4,
+}
+
+impl Dfsr {
+    /// Reads the *Data Fault Status Register*
+    #[inline]
+    pub fn read() -> Dfsr {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c5, c0, 0", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+
+    /// Write to the *Data Fault Status Register*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 0, {}, c5, c0, 0", in(reg) _value.raw_value(), options(nomem, nostack, preserves_flags));
+        };
+    }
+
+    /// The five-bit Fault Status code
+    #[inline]
+    pub fn fs(&self) -> u5 {
+        u5::new(((self.fs_hi() as u8) << 4) | self.fs_lo().value())
+    }
+}
+
+impl core::fmt::Debug for Dfsr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "DFSR {{ ExT={} WnR={} FS={:#07b} }}",
+            self.ext() as u8,
+            self.wnr() as u8,
+            self.fs().value()
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Dfsr {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "DFSR {{ ExT={0=12..13} WnR={0=11..12} FS[4]={0=10..11} FS[3:0]={0=0..4:04b} }}",
+            self.0
+        )
+    }
+}
@@ -0,0 +1,93 @@
+//! Code for managing the *MPU Region Access Control Register*
+
+use arbitrary_int::u3;
+
+/// The *MPU Region Access Control Register* (DRACR)
+///
+/// Controls the region selected by [`Rgnr`](super::Rgnr).
+#[bitbybit::bitfield(u32)]
+pub struct Dracr {
+    /// Execute Never
+    #[bits(12..=12, rw)]
+    xn: bool,
+    /// Access Permissions
+    #[bits(8..=10, rw)]
+    ap: u3,
+    /// Type Extension
+    #[bits(3..=5, rw)]
+    tex: u3,
+    /// Shareable
+    #[bits(2..=2, rw)]
+    s: bool,
+    /// Cacheable
+    #[bits(1..=1, rw)]
+    c: bool,
+    /// Bufferable
+    #[bits(0..=0, rw)]
+    b: bool,
+}
+
+impl Dracr {
+    /// Reads the *MPU Region Access Control Register*
+    #[inline]
+    pub fn read() -> Dracr {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c6, c1, 4", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+
+    /// Write to the *MPU Region Access Control Register*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 0, {}, c6, c1, 4", in(reg) _value.raw_value(), options(nomem, nostack, preserves_flags));
+        };
+    }
+
+    /// Modify the *MPU Region Access Control Register*
+    #[inline]
+    pub fn modify<F>(f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let mut value = Self::read();
+        f(&mut value);
+        Self::write(value);
+    }
+}
+
+impl core::fmt::Debug for Dracr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "DRACR {{ XN={} AP=0b{:03b} TEX=0b{:03b} S={} C={} B={} }}",
+            self.xn() as u8,
+            self.ap().value(),
+            self.tex().value(),
+            self.s() as u8,
+            self.c() as u8,
+            self.b() as u8
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Dracr {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "DRACR {{ XN={0=12..13} AP=0b{0=8..11:03b} TEX=0b{0=3..6:03b} S={0=2..3} C={0=1..2} B={0=0..1} }}",
+            self.0
+        )
+    }
+}
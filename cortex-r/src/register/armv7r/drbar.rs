@@ -0,0 +1,52 @@
+//! Code for managing the *MPU Region Base Address Register*
+
+/// The *MPU Region Base Address Register* (DRBAR)
+///
+/// Holds the base address of the region selected by [`Rgnr`](super::Rgnr).
+/// The bottom five bits are always zero.
+///
+/// There is no `modify` method because this register holds a single 32-bit address.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Drbar(pub u32);
+
+impl Drbar {
+    /// Reads the *MPU Region Base Address Register*
+    #[inline]
+    pub fn read() -> Drbar {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c6, c1, 0", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self(r)
+    }
+
+    /// Write to the *MPU Region Base Address Register*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 0, {}, c6, c1, 0", in(reg) _value.0, options(nomem, nostack, preserves_flags));
+        };
+    }
+}
+
+impl core::fmt::Debug for Drbar {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "DRBAR {{ 0x{:08x} }}", self.0)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Drbar {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "DRBAR {{ 0x{=u32:08x} }}", self.0)
+    }
+}
@@ -0,0 +1,81 @@
+//! Code for managing the *MPU Region Size and Enable Register*
+
+use arbitrary_int::u5;
+
+/// The *MPU Region Size and Enable Register* (DRSR)
+///
+/// Controls the region selected by [`Rgnr`](super::Rgnr).
+#[bitbybit::bitfield(u32)]
+pub struct Drsr {
+    /// Subregion Disable - set bit N to disable subregion N
+    #[bits(8..=15, rw)]
+    sd: u8,
+    /// Region Size, where the size in bytes is `2 ** (rsize + 1)`
+    #[bits(1..=5, rw)]
+    rsize: u5,
+    /// Region Enable
+    #[bits(0..=0, rw)]
+    en: bool,
+}
+
+impl Drsr {
+    /// Reads the *MPU Region Size and Enable Register*
+    #[inline]
+    pub fn read() -> Drsr {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c6, c1, 2", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+
+    /// Write to the *MPU Region Size and Enable Register*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 0, {}, c6, c1, 2", in(reg) _value.raw_value(), options(nomem, nostack, preserves_flags));
+        };
+    }
+
+    /// Modify the *MPU Region Size and Enable Register*
+    #[inline]
+    pub fn modify<F>(f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let mut value = Self::read();
+        f(&mut value);
+        Self::write(value);
+    }
+}
+
+impl core::fmt::Debug for Drsr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "DRSR {{ SD=0b{:08b} RSIZE={} EN={} }}",
+            self.sd(),
+            self.rsize(),
+            self.en() as u8
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Drsr {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "DRSR {{ SD=0b{0=8..16:08b} RSIZE={0=1..6} EN={0=0..1} }}",
+            self.0
+        )
+    }
+}
@@ -0,0 +1,76 @@
+//! Code for managing the *Instruction Fault Status Register*
+
+use arbitrary_int::{u4, u5};
+
+/// The *Instruction Fault Status Register* (IFSR)
+///
+/// Holds the cause of the most recent Prefetch Abort.
+#[bitbybit::bitfield(u32)]
+pub struct Ifsr {
+    /// External abort type (SLVERR when set, DECERR when clear, on the
+    /// Cortex-R5)
+    #[bits(12..=12, rw)]
+    ext: bool,
+    /// Fault Status bit 4
+    #[bits(10..=10, rw)]
+    fs_hi: bool,
+    /// Fault Status bits 3 to 0
+    #[bits(0..=3, rw)]
+    fs_lo: u4,
+}
+
+impl Ifsr {
+    /// Reads the *Instruction Fault Status Register*
+    #[inline]
+    pub fn read() -> Ifsr {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c5, c0, 1", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+
+    /// Write to the *Instruction Fault Status Register*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 0, {}, c5, c0, 1", in(reg) _value.raw_value(), options(nomem, nostack, preserves_flags));
+        };
+    }
+
+    /// The five-bit Fault Status code
+    #[inline]
+    pub fn fs(&self) -> u5 {
+        u5::new(((self.fs_hi() as u8) << 4) | self.fs_lo().value())
+    }
+}
+
+impl core::fmt::Debug for Ifsr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "IFSR {{ ExT={} FS={:#07b} }}",
+            self.ext() as u8,
+            self.fs().value()
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Ifsr {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "IFSR {{ ExT={0=12..13} FS[4]={0=10..11} FS[3:0]={0=0..4:04b} }}",
+            self.0
+        )
+    }
+}
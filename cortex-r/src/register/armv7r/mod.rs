@@ -0,0 +1,25 @@
+//! Access registers for Armv7-R only
+
+mod dfsr;
+#[doc(inline)]
+pub use dfsr::Dfsr;
+
+mod ifsr;
+#[doc(inline)]
+pub use ifsr::Ifsr;
+
+mod dracr;
+#[doc(inline)]
+pub use dracr::Dracr;
+
+mod drbar;
+#[doc(inline)]
+pub use drbar::Drbar;
+
+mod drsr;
+#[doc(inline)]
+pub use drsr::Drsr;
+
+mod rgnr;
+#[doc(inline)]
+pub use rgnr::Rgnr;
@@ -0,0 +1,53 @@
+//! Code for managing the *MPU Region Number Register*
+
+/// The *MPU Region Number Register* (RGNR)
+///
+/// Selects which region is accessed through [`Drbar`](super::Drbar),
+/// [`Drsr`](super::Drsr) and [`Dracr`](super::Dracr).
+#[bitbybit::bitfield(u32)]
+pub struct Rgnr {
+    /// The currently selected MPU region
+    #[bits(0..=7, rw)]
+    region: u8,
+}
+
+impl Rgnr {
+    /// Reads the *MPU Region Number Register*
+    #[inline]
+    pub fn read() -> Rgnr {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c6, c2, 0", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+
+    /// Write to the *MPU Region Number Register*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 0, {}, c6, c2, 0", in(reg) _value.raw_value(), options(nomem, nostack, preserves_flags));
+        };
+    }
+}
+
+impl core::fmt::Debug for Rgnr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "RGNR {{ REGION={} }}", self.region())
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Rgnr {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "RGNR {{ REGION={0=0..8} }}", self.0)
+    }
+}
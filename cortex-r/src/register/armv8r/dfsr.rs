@@ -0,0 +1,83 @@
+//! Code for managing the *Data Fault Status Register*
+
+use arbitrary_int::u6;
+
+/// The *Data Fault Status Register* (DFSR)
+///
+/// Holds the cause of the most recent Data Abort. Armv8-R always uses the
+/// long-descriptor format of this register.
+#[bitbybit::bitfield(u32)]
+pub struct Dfsr {
+    /// FAR not Valid
+    #[bits(16..=16, rw)]
+    fnv: bool,
+    /// Cache Maintenance
+    #[bits(13..=13, rw)]
+    cm: bool,
+    /// External abort type
+    #[bits(12..=12, rw)]
+    ext: bool,
+    /// Write not Read
+    #[bits(11..=11, rw)]
+    wnr: bool,
+    /// Long-descriptor format (always set)
+    #[bits(9..=9, rw)]
+    lpae: bool,
+    /// Fault Status code
+    #[bits(0..=5, rw)]
+    status: u6,
+}
+
+impl Dfsr {
+    /// Reads the *Data Fault Status Register*
+    #[inline]
+    pub fn read() -> Dfsr {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c5, c0, 0", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+
+    /// Write to the *Data Fault Status Register*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 0, {}, c5, c0, 0", in(reg) _value.raw_value(), options(nomem, nostack, preserves_flags));
+        };
+    }
+}
+
+impl core::fmt::Debug for Dfsr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "DFSR {{ FnV={} CM={} ExT={} WnR={} LPAE={} STATUS={:#08b} }}",
+            self.fnv() as u8,
+            self.cm() as u8,
+            self.ext() as u8,
+            self.wnr() as u8,
+            self.lpae() as u8,
+            self.status().value()
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Dfsr {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "DFSR {{ FnV={0=16..17} CM={0=13..14} ExT={0=12..13} WnR={0=11..12} LPAE={0=9..10} STATUS={0=0..6:06b} }}",
+            self.0
+        )
+    }
+}
@@ -0,0 +1,86 @@
+//! Code for managing the *Hyp Memory Attribute Indirection Register 0*
+
+/// The *Hyp Memory Attribute Indirection Register 0* (HMAIR0)
+///
+/// Holds memory attributes 0 to 3, which EL2 MPU regions select with the
+/// `ATTRINDX` field of [`Hprlar`](super::Hprlar).
+///
+/// This is only available in EL2.
+#[bitbybit::bitfield(u32)]
+pub struct Hmair0 {
+    /// Memory attribute 3
+    #[bits(24..=31, rw)]
+    attr3: u8,
+    /// Memory attribute 2
+    #[bits(16..=23, rw)]
+    attr2: u8,
+    /// Memory attribute 1
+    #[bits(8..=15, rw)]
+    attr1: u8,
+    /// Memory attribute 0
+    #[bits(0..=7, rw)]
+    attr0: u8,
+}
+
+impl Hmair0 {
+    /// Reads the *Hyp Memory Attribute Indirection Register 0*
+    #[inline]
+    pub fn read() -> Hmair0 {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 4, {}, c10, c2, 0", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+
+    /// Write to the *Hyp Memory Attribute Indirection Register 0*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 4, {}, c10, c2, 0", in(reg) _value.raw_value(), options(nomem, nostack, preserves_flags));
+        };
+    }
+
+    /// Modify the *Hyp Memory Attribute Indirection Register 0*
+    #[inline]
+    pub fn modify<F>(f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let mut value = Self::read();
+        f(&mut value);
+        Self::write(value);
+    }
+}
+
+impl core::fmt::Debug for Hmair0 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "HMAIR0 {{ ATTR0=0x{:02x} ATTR1=0x{:02x} ATTR2=0x{:02x} ATTR3=0x{:02x} }}",
+            self.attr0(),
+            self.attr1(),
+            self.attr2(),
+            self.attr3()
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Hmair0 {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "HMAIR0 {{ ATTR0=0x{0=0..8:02x} ATTR1=0x{0=8..16:02x} ATTR2=0x{0=16..24:02x} ATTR3=0x{0=24..32:02x} }}",
+            self.0
+        )
+    }
+}
@@ -0,0 +1,42 @@
+//! Code for managing the *Hyp MPU Type Register*
+
+/// The *Hyp MPU Type Register* (HMPUIR)
+///
+/// This is only available in EL2.
+#[bitbybit::bitfield(u32)]
+pub struct Hmpuir {
+    /// The number of EL2 MPU regions
+    #[bits(0..=7, r)]
+    region: u8,
+}
+
+impl Hmpuir {
+    /// Reads the *Hyp MPU Type Register*
+    #[inline]
+    pub fn read() -> Hmpuir {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 4, {}, c0, c0, 4", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+}
+
+impl core::fmt::Debug for Hmpuir {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "HMPUIR {{ REGION={} }}", self.region())
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Hmpuir {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "HMPUIR {{ REGION={0=0..8} }}", self.0)
+    }
+}
@@ -0,0 +1,88 @@
+//! Code for managing the *Hyp Protection Region Base Address Register*
+
+use arbitrary_int::{u2, u26};
+
+/// The *Hyp Protection Region Base Address Register* (HPRBAR)
+///
+/// Controls the EL2 MPU region selected by [`Hprselr`](super::Hprselr).
+///
+/// This is only available in EL2.
+#[bitbybit::bitfield(u32)]
+pub struct Hprbar {
+    /// Bits 31:6 of the lowest address in the region
+    #[bits(6..=31, rw)]
+    base: u26,
+    /// Shareability
+    #[bits(3..=4, rw)]
+    sh: u2,
+    /// Access Permissions
+    #[bits(1..=2, rw)]
+    ap: u2,
+    /// Execute Never
+    #[bits(0..=0, rw)]
+    xn: bool,
+}
+
+impl Hprbar {
+    /// Reads the *Hyp Protection Region Base Address Register*
+    #[inline]
+    pub fn read() -> Hprbar {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 4, {}, c6, c3, 0", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+
+    /// Write to the *Hyp Protection Region Base Address Register*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 4, {}, c6, c3, 0", in(reg) _value.raw_value(), options(nomem, nostack, preserves_flags));
+        };
+    }
+
+    /// Modify the *Hyp Protection Region Base Address Register*
+    #[inline]
+    pub fn modify<F>(f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let mut value = Self::read();
+        f(&mut value);
+        Self::write(value);
+    }
+}
+
+impl core::fmt::Debug for Hprbar {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "HPRBAR {{ BASE=0x{:08x} SH=0b{:02b} AP=0b{:02b} XN={} }}",
+            self.base().value() << 6,
+            self.sh().value(),
+            self.ap().value(),
+            self.xn() as u8
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Hprbar {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "HPRBAR {{ BASE=0x{0=u32:08x} SH={1=3..5} AP={1=1..3} XN={1=0..1} }}",
+            self.0 & !0x3F,
+            self.0
+        )
+    }
+}
@@ -0,0 +1,52 @@
+//! Code for managing the *Hyp MPU Region Enable Register*
+
+/// The *Hyp MPU Region Enable Register* (HPRENR)
+///
+/// Bit N reflects, and controls, the enable bit of EL2 MPU region N, for the
+/// first 32 regions.
+///
+/// This is only available in EL2.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Hprenr(pub u32);
+
+impl Hprenr {
+    /// Reads the *Hyp MPU Region Enable Register*
+    #[inline]
+    pub fn read() -> Hprenr {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 4, {}, c6, c1, 1", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self(r)
+    }
+
+    /// Write to the *Hyp MPU Region Enable Register*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 4, {}, c6, c1, 1", in(reg) _value.0, options(nomem, nostack, preserves_flags));
+        };
+    }
+}
+
+impl core::fmt::Debug for Hprenr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "HPRENR {{ 0b{:032b} }}", self.0)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Hprenr {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "HPRENR {{ 0b{=u32:032b} }}", self.0)
+    }
+}
@@ -0,0 +1,85 @@
+//! Code for managing the *Hyp Protection Region Limit Address Register*
+
+use arbitrary_int::{u26, u3};
+
+/// The *Hyp Protection Region Limit Address Register* (HPRLAR)
+///
+/// Controls the EL2 MPU region selected by [`Hprselr`](super::Hprselr).
+///
+/// This is only available in EL2.
+#[bitbybit::bitfield(u32)]
+pub struct Hprlar {
+    /// Bits 31:6 of the highest address in the region (the lower six bits are
+    /// taken to be all ones)
+    #[bits(6..=31, rw)]
+    limit: u26,
+    /// Index into the HMAIR0/HMAIR1 memory attributes
+    #[bits(1..=3, rw)]
+    attrindx: u3,
+    /// Region Enable
+    #[bits(0..=0, rw)]
+    en: bool,
+}
+
+impl Hprlar {
+    /// Reads the *Hyp Protection Region Limit Address Register*
+    #[inline]
+    pub fn read() -> Hprlar {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 4, {}, c6, c3, 1", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+
+    /// Write to the *Hyp Protection Region Limit Address Register*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 4, {}, c6, c3, 1", in(reg) _value.raw_value(), options(nomem, nostack, preserves_flags));
+        };
+    }
+
+    /// Modify the *Hyp Protection Region Limit Address Register*
+    #[inline]
+    pub fn modify<F>(f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let mut value = Self::read();
+        f(&mut value);
+        Self::write(value);
+    }
+}
+
+impl core::fmt::Debug for Hprlar {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "HPRLAR {{ LIMIT=0x{:08x} ATTRINDX={} EN={} }}",
+            (self.limit().value() << 6) | 0x3F,
+            self.attrindx(),
+            self.en() as u8
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Hprlar {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "HPRLAR {{ LIMIT=0x{0=u32:08x} ATTRINDX={1=1..4} EN={1=0..1} }}",
+            self.0 | 0x3F,
+            self.0
+        )
+    }
+}
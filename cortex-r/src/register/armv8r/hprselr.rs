@@ -0,0 +1,55 @@
+//! Code for managing the *Hyp Protection Region Selection Register*
+
+/// The *Hyp Protection Region Selection Register* (HPRSELR)
+///
+/// Selects which EL2 MPU region is accessed through [`Hprbar`](super::Hprbar)
+/// and [`Hprlar`](super::Hprlar).
+///
+/// This is only available in EL2.
+#[bitbybit::bitfield(u32)]
+pub struct Hprselr {
+    /// The currently selected MPU region
+    #[bits(0..=7, rw)]
+    region: u8,
+}
+
+impl Hprselr {
+    /// Reads the *Hyp Protection Region Selection Register*
+    #[inline]
+    pub fn read() -> Hprselr {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 4, {}, c6, c2, 1", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+
+    /// Write to the *Hyp Protection Region Selection Register*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 4, {}, c6, c2, 1", in(reg) _value.raw_value(), options(nomem, nostack, preserves_flags));
+        };
+    }
+}
+
+impl core::fmt::Debug for Hprselr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "HPRSELR {{ REGION={} }}", self.region())
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Hprselr {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "HPRSELR {{ REGION={0=0..8} }}", self.0)
+    }
+}
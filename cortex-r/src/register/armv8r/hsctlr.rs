@@ -0,0 +1,99 @@
+//! Code for managing the *Hyp System Control Register*
+
+/// The *Hyp System Control Register* (HSCTLR)
+///
+/// This is only available in EL2.
+#[bitbybit::bitfield(u32)]
+pub struct Hsctlr {
+    /// The bitmask for the Thumb Exception Enable bit
+    #[bits(30..=30, rw)]
+    te: bool,
+    /// The bitmask for the Exception Endianness bit
+    #[bits(25..=25, rw)]
+    ee: bool,
+    /// The bitmask for the Fast Interrupt bit
+    #[bits(21..=21, rw)]
+    fi: bool,
+    /// The bitmask for the Write-implies-XN bit
+    #[bits(19..=19, rw)]
+    wxn: bool,
+    /// The bitmask for the Background Region bit
+    #[bits(17..=17, rw)]
+    br: bool,
+    /// The bitmask for the Instruction Cache enable bit
+    #[bits(12..=12, rw)]
+    i: bool,
+    /// The bitmask for the Cache enable bit
+    #[bits(2..=2, rw)]
+    c: bool,
+    /// The bitmask for the Alignment check bit
+    #[bits(1..=1, rw)]
+    a: bool,
+    /// The bitmask for the MPU bit
+    #[bits(0..=0, rw)]
+    m: bool,
+}
+
+impl Hsctlr {
+    /// Reads the *Hyp System Control Register*
+    #[inline]
+    pub fn read() -> Self {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 4, {}, c1, c0, 0", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+
+    /// Write to the *Hyp System Control Register*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 4, {}, c1, c0, 0", in(reg) _value.raw_value(), options(nomem, nostack, preserves_flags));
+        };
+    }
+
+    /// Modify the *Hyp System Control Register*
+    #[inline]
+    pub fn modify<F>(f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let mut value = Self::read();
+        f(&mut value);
+        Self::write(value);
+    }
+}
+
+impl core::fmt::Debug for Hsctlr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "HSCTLR {{ TE={} EE={} FI={} WXN={} BR={} I={} C={} A={} M={} }}",
+            self.te() as u8,
+            self.ee() as u8,
+            self.fi() as u8,
+            self.wxn() as u8,
+            self.br() as u8,
+            self.i() as u8,
+            self.c() as u8,
+            self.a() as u8,
+            self.m() as u8,
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Hsctlr {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "HSCTLR {{ TE={0=30..31} EE={0=25..26} FI={0=21..22} WXN={0=19..20} BR={0=17..18} I={0=12..13} C={0=2..3} A={0=1..2} M={0=0..1} }}", self.0)
+    }
+}
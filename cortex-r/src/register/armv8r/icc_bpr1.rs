@@ -0,0 +1,52 @@
+//! Code for managing the *Interrupt Controller Binary Point Register 1*
+
+use arbitrary_int::u3;
+
+/// The *Interrupt Controller Binary Point Register 1* (ICC_BPR1)
+#[bitbybit::bitfield(u32)]
+pub struct IccBpr1 {
+    /// Splits the priority into group priority (for preemption) and subpriority
+    #[bits(0..=2, rw)]
+    binary_point: u3,
+}
+
+impl IccBpr1 {
+    /// Reads the *Interrupt Controller Binary Point Register 1*
+    #[inline]
+    pub fn read() -> IccBpr1 {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c12, c12, 3", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+
+    /// Write to the *Interrupt Controller Binary Point Register 1*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 0, {}, c12, c12, 3", in(reg) _value.raw_value(), options(nomem, nostack, preserves_flags));
+        };
+    }
+}
+
+impl core::fmt::Debug for IccBpr1 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ICC_BPR1 {{ BinaryPoint={} }}", self.binary_point())
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for IccBpr1 {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "ICC_BPR1 {{ BinaryPoint={0=0..3} }}", self.0)
+    }
+}
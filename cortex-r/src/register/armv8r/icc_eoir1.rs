@@ -0,0 +1,38 @@
+//! Code for managing the *Interrupt Controller End Of Interrupt Register 1*
+
+use arbitrary_int::u24;
+
+/// The *Interrupt Controller End Of Interrupt Register 1* (ICC_EOIR1)
+///
+/// Writing this register marks a Group 1 interrupt as no longer active.
+#[bitbybit::bitfield(u32)]
+pub struct IccEoir1 {
+    /// The ID of the interrupt which has been handled
+    #[bits(0..=23, rw)]
+    intid: u24,
+}
+
+impl IccEoir1 {
+    /// Write to the *Interrupt Controller End Of Interrupt Register 1*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 0, {}, c12, c12, 1", in(reg) _value.raw_value(), options(nostack, preserves_flags));
+        };
+    }
+}
+
+impl core::fmt::Debug for IccEoir1 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ICC_EOIR1 {{ INTID={} }}", self.intid())
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for IccEoir1 {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "ICC_EOIR1 {{ INTID={0=0..24} }}", self.0)
+    }
+}
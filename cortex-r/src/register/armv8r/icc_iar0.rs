@@ -0,0 +1,48 @@
+//! Code for managing the *Interrupt Controller Interrupt Acknowledge Register 0*
+
+use arbitrary_int::u24;
+
+/// The *Interrupt Controller Interrupt Acknowledge Register 0* (ICC_IAR0)
+///
+/// Reading this register acknowledges the highest priority pending Group 0
+/// interrupt.
+#[bitbybit::bitfield(u32)]
+pub struct IccIar0 {
+    /// The ID of the acknowledged interrupt (1023 if there was none)
+    #[bits(0..=23, r)]
+    intid: u24,
+}
+
+impl IccIar0 {
+    /// Reads the *Interrupt Controller Interrupt Acknowledge Register 0*
+    ///
+    /// This acknowledges the interrupt, which becomes active.
+    #[inline]
+    pub fn read() -> IccIar0 {
+        let r: u32;
+        // Safety: Reading this register is atomic. The side-effect (of
+        // acknowledging an interrupt) does not affect memory safety.
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c12, c8, 0", out(reg) r, options(nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+}
+
+impl core::fmt::Debug for IccIar0 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ICC_IAR0 {{ INTID={} }}", self.intid())
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for IccIar0 {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "ICC_IAR0 {{ INTID={0=0..24} }}", self.0)
+    }
+}
@@ -0,0 +1,50 @@
+//! Code for managing the *Interrupt Controller Interrupt Group 1 Enable Register*
+
+/// The *Interrupt Controller Interrupt Group 1 Enable Register* (ICC_IGRPEN1)
+#[bitbybit::bitfield(u32)]
+pub struct IccIgrpen1 {
+    /// Group 1 interrupts are enabled
+    #[bits(0..=0, rw)]
+    enable: bool,
+}
+
+impl IccIgrpen1 {
+    /// Reads the *Interrupt Controller Interrupt Group 1 Enable Register*
+    #[inline]
+    pub fn read() -> IccIgrpen1 {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c12, c12, 7", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+
+    /// Write to the *Interrupt Controller Interrupt Group 1 Enable Register*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 0, {}, c12, c12, 7", in(reg) _value.raw_value(), options(nomem, nostack, preserves_flags));
+        };
+    }
+}
+
+impl core::fmt::Debug for IccIgrpen1 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ICC_IGRPEN1 {{ Enable={} }}", self.enable() as u8)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for IccIgrpen1 {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "ICC_IGRPEN1 {{ Enable={0=0..1} }}", self.0)
+    }
+}
@@ -0,0 +1,51 @@
+//! Code for managing the *Interrupt Controller Interrupt Priority Mask Register*
+
+/// The *Interrupt Controller Interrupt Priority Mask Register* (ICC_PMR)
+#[bitbybit::bitfield(u32)]
+pub struct IccPmr {
+    /// The priority mask. Only interrupts with a higher priority (i.e. a lower
+    /// value) than this are signalled.
+    #[bits(0..=7, rw)]
+    priority: u8,
+}
+
+impl IccPmr {
+    /// Reads the *Interrupt Controller Interrupt Priority Mask Register*
+    #[inline]
+    pub fn read() -> IccPmr {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c4, c6, 0", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+
+    /// Write to the *Interrupt Controller Interrupt Priority Mask Register*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 0, {}, c4, c6, 0", in(reg) _value.raw_value(), options(nomem, nostack, preserves_flags));
+        };
+    }
+}
+
+impl core::fmt::Debug for IccPmr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ICC_PMR {{ Priority={} }}", self.priority())
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for IccPmr {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "ICC_PMR {{ Priority={0=0..8} }}", self.0)
+    }
+}
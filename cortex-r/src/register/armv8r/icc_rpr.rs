@@ -0,0 +1,40 @@
+//! Code for managing the *Interrupt Controller Running Priority Register*
+
+/// The *Interrupt Controller Running Priority Register* (ICC_RPR)
+#[bitbybit::bitfield(u32)]
+pub struct IccRpr {
+    /// The priority of the highest priority active interrupt (0xFF if there is none)
+    #[bits(0..=7, r)]
+    priority: u8,
+}
+
+impl IccRpr {
+    /// Reads the *Interrupt Controller Running Priority Register*
+    #[inline]
+    pub fn read() -> IccRpr {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c12, c11, 3", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+}
+
+impl core::fmt::Debug for IccRpr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ICC_RPR {{ Priority={} }}", self.priority())
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for IccRpr {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "ICC_RPR {{ Priority={0=0..8} }}", self.0)
+    }
+}
@@ -0,0 +1,70 @@
+//! Code for managing the *Interrupt Controller Software Generated Interrupt Group 1 Register*
+
+use arbitrary_int::u4;
+
+/// The *Interrupt Controller Software Generated Interrupt Group 1 Register*
+/// (ICC_SGI1R)
+///
+/// Writing this 64-bit register generates a Group 1 SGI.
+#[bitbybit::bitfield(u64)]
+pub struct IccSgi1r {
+    /// Affinity level 3 of the target cores
+    #[bits(48..=55, rw)]
+    aff3: u8,
+    /// Interrupt Routing Mode. If set, the SGI goes to every core except this
+    /// one and the affinity fields are ignored.
+    #[bits(40..=40, rw)]
+    irm: bool,
+    /// Affinity level 2 of the target cores
+    #[bits(32..=39, rw)]
+    aff2: u8,
+    /// The ID of the SGI to generate
+    #[bits(24..=27, rw)]
+    intid: u4,
+    /// Affinity level 1 of the target cores
+    #[bits(16..=23, rw)]
+    aff1: u8,
+    /// One bit for each affinity level 0 value of the target cores
+    #[bits(0..=15, rw)]
+    target_list: u16,
+}
+
+impl IccSgi1r {
+    /// Write to the *Interrupt Controller Software Generated Interrupt Group 1
+    /// Register*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            let raw = _value.raw_value();
+            core::arch::asm!("mcrr p15, 0, {}, {}, c12", in(reg) raw as u32, in(reg) (raw >> 32) as u32, options(nostack, preserves_flags));
+        };
+    }
+}
+
+impl core::fmt::Debug for IccSgi1r {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "ICC_SGI1R {{ Aff3={} IRM={} Aff2={} INTID={} Aff1={} TargetList={:#018b} }}",
+            self.aff3(),
+            self.irm() as u8,
+            self.aff2(),
+            self.intid(),
+            self.aff1(),
+            self.target_list()
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for IccSgi1r {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "ICC_SGI1R {{ Aff3={0=48..56} IRM={0=40..41} Aff2={0=32..40} INTID={0=24..28} Aff1={0=16..24} TargetList={0=0..16:016b} }}",
+            self.0
+        )
+    }
+}
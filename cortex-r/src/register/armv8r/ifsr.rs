@@ -0,0 +1,71 @@
+//! Code for managing the *Instruction Fault Status Register*
+
+use arbitrary_int::u6;
+
+/// The *Instruction Fault Status Register* (IFSR)
+///
+/// Holds the cause of the most recent Prefetch Abort. Armv8-R always uses the
+/// long-descriptor format of this register.
+#[bitbybit::bitfield(u32)]
+pub struct Ifsr {
+    /// External abort type
+    #[bits(12..=12, rw)]
+    ext: bool,
+    /// Long-descriptor format (always set)
+    #[bits(9..=9, rw)]
+    lpae: bool,
+    /// Fault Status code
+    #[bits(0..=5, rw)]
+    status: u6,
+}
+
+impl Ifsr {
+    /// Reads the *Instruction Fault Status Register*
+    #[inline]
+    pub fn read() -> Ifsr {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c5, c0, 1", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+
+    /// Write to the *Instruction Fault Status Register*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 0, {}, c5, c0, 1", in(reg) _value.raw_value(), options(nomem, nostack, preserves_flags));
+        };
+    }
+}
+
+impl core::fmt::Debug for Ifsr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "IFSR {{ ExT={} LPAE={} STATUS={:#08b} }}",
+            self.ext() as u8,
+            self.lpae() as u8,
+            self.status().value()
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Ifsr {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "IFSR {{ ExT={0=12..13} LPAE={0=9..10} STATUS={0=0..6:06b} }}",
+            self.0
+        )
+    }
+}
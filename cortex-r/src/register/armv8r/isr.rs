@@ -0,0 +1,54 @@
+//! Code for managing the *Interrupt Status Register*
+
+/// The *Interrupt Status Register* (ISR)
+///
+/// Shows which exceptions are pending, whether or not they are masked.
+#[bitbybit::bitfield(u32)]
+pub struct Isr {
+    /// An asynchronous abort (SError) is pending
+    #[bits(8..=8, r)]
+    a: bool,
+    /// An IRQ is pending
+    #[bits(7..=7, r)]
+    i: bool,
+    /// An FIQ is pending
+    #[bits(6..=6, r)]
+    f: bool,
+}
+
+impl Isr {
+    /// Reads the *Interrupt Status Register*
+    #[inline]
+    pub fn read() -> Isr {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c12, c1, 0", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+}
+
+impl core::fmt::Debug for Isr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "ISR {{ A={} I={} F={} }}",
+            self.a() as u8,
+            self.i() as u8,
+            self.f() as u8
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Isr {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "ISR {{ A={0=8..9} I={0=7..8} F={0=6..7} }}", self.0)
+    }
+}
@@ -0,0 +1,84 @@
+//! Code for managing the *Memory Attribute Indirection Register 1*
+
+/// The *Memory Attribute Indirection Register 1* (MAIR1)
+///
+/// Holds memory attributes 4 to 7, which EL1 MPU regions select with the
+/// `ATTRINDX` field of [`Prlar`](super::Prlar).
+#[bitbybit::bitfield(u32)]
+pub struct Mair1 {
+    /// Memory attribute 7
+    #[bits(24..=31, rw)]
+    attr7: u8,
+    /// Memory attribute 6
+    #[bits(16..=23, rw)]
+    attr6: u8,
+    /// Memory attribute 5
+    #[bits(8..=15, rw)]
+    attr5: u8,
+    /// Memory attribute 4
+    #[bits(0..=7, rw)]
+    attr4: u8,
+}
+
+impl Mair1 {
+    /// Reads the *Memory Attribute Indirection Register 1*
+    #[inline]
+    pub fn read() -> Mair1 {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c10, c2, 1", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+
+    /// Write to the *Memory Attribute Indirection Register 1*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 0, {}, c10, c2, 1", in(reg) _value.raw_value(), options(nomem, nostack, preserves_flags));
+        };
+    }
+
+    /// Modify the *Memory Attribute Indirection Register 1*
+    #[inline]
+    pub fn modify<F>(f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let mut value = Self::read();
+        f(&mut value);
+        Self::write(value);
+    }
+}
+
+impl core::fmt::Debug for Mair1 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "MAIR1 {{ ATTR4=0x{:02x} ATTR5=0x{:02x} ATTR6=0x{:02x} ATTR7=0x{:02x} }}",
+            self.attr4(),
+            self.attr5(),
+            self.attr6(),
+            self.attr7()
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Mair1 {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "MAIR1 {{ ATTR4=0x{0=0..8:02x} ATTR5=0x{0=8..16:02x} ATTR6=0x{0=16..24:02x} ATTR7=0x{0=24..32:02x} }}",
+            self.0
+        )
+    }
+}
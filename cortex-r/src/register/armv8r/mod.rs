@@ -4,14 +4,118 @@ mod cbar;
 #[doc(inline)]
 pub use cbar::Cbar;
 
+mod dfsr;
+#[doc(inline)]
+pub use dfsr::Dfsr;
+
+mod ifsr;
+#[doc(inline)]
+pub use ifsr::Ifsr;
+
 mod hactlr;
 #[doc(inline)]
 pub use hactlr::Hactlr;
 
+mod hmair0;
+#[doc(inline)]
+pub use hmair0::Hmair0;
+
+mod hmair1;
+#[doc(inline)]
+pub use hmair1::Hmair1;
+
+mod hmpuir;
+#[doc(inline)]
+pub use hmpuir::Hmpuir;
+
+mod hprbar;
+#[doc(inline)]
+pub use hprbar::Hprbar;
+
+mod hprenr;
+#[doc(inline)]
+pub use hprenr::Hprenr;
+
+mod hprlar;
+#[doc(inline)]
+pub use hprlar::Hprlar;
+
+mod hprselr;
+#[doc(inline)]
+pub use hprselr::Hprselr;
+
+mod hsctlr;
+#[doc(inline)]
+pub use hsctlr::Hsctlr;
+
 mod hvbar;
 #[doc(inline)]
 pub use hvbar::Hvbar;
 
+mod icc_bpr1;
+#[doc(inline)]
+pub use icc_bpr1::IccBpr1;
+
+mod icc_eoir0;
+#[doc(inline)]
+pub use icc_eoir0::IccEoir0;
+
+mod icc_eoir1;
+#[doc(inline)]
+pub use icc_eoir1::IccEoir1;
+
+mod icc_iar0;
+#[doc(inline)]
+pub use icc_iar0::IccIar0;
+
+mod icc_iar1;
+#[doc(inline)]
+pub use icc_iar1::IccIar1;
+
+mod icc_igrpen0;
+#[doc(inline)]
+pub use icc_igrpen0::IccIgrpen0;
+
+mod icc_igrpen1;
+#[doc(inline)]
+pub use icc_igrpen1::IccIgrpen1;
+
+mod icc_pmr;
+#[doc(inline)]
+pub use icc_pmr::IccPmr;
+
+mod icc_rpr;
+#[doc(inline)]
+pub use icc_rpr::IccRpr;
+
+mod icc_sgi1r;
+#[doc(inline)]
+pub use icc_sgi1r::IccSgi1r;
+
+mod isr;
+#[doc(inline)]
+pub use isr::Isr;
+
+mod mair0;
+#[doc(inline)]
+pub use mair0::Mair0;
+
+mod mair1;
+#[doc(inline)]
+pub use mair1::Mair1;
+
+mod prbar;
+#[doc(inline)]
+pub use prbar::Prbar;
+
+mod prlar;
+#[doc(inline)]
+pub use prlar::Prlar;
+
+mod prselr;
+#[doc(inline)]
+pub use prselr::Prselr;
+
 mod vbar;
 #[doc(inline)]
 pub use vbar::Vbar;
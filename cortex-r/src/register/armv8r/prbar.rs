@@ -0,0 +1,86 @@
+//! Code for managing the *Protection Region Base Address Register*
+
+use arbitrary_int::{u2, u26};
+
+/// The *Protection Region Base Address Register* (PRBAR)
+///
+/// Controls the EL1 MPU region selected by [`Prselr`](super::Prselr).
+#[bitbybit::bitfield(u32)]
+pub struct Prbar {
+    /// Bits 31:6 of the lowest address in the region
+    #[bits(6..=31, rw)]
+    base: u26,
+    /// Shareability
+    #[bits(3..=4, rw)]
+    sh: u2,
+    /// Access Permissions
+    #[bits(1..=2, rw)]
+    ap: u2,
+    /// Execute Never
+    #[bits(0..=0, rw)]
+    xn: bool,
+}
+
+impl Prbar {
+    /// Reads the *Protection Region Base Address Register*
+    #[inline]
+    pub fn read() -> Prbar {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c6, c3, 0", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+
+    /// Write to the *Protection Region Base Address Register*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 0, {}, c6, c3, 0", in(reg) _value.raw_value(), options(nomem, nostack, preserves_flags));
+        };
+    }
+
+    /// Modify the *Protection Region Base Address Register*
+    #[inline]
+    pub fn modify<F>(f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let mut value = Self::read();
+        f(&mut value);
+        Self::write(value);
+    }
+}
+
+impl core::fmt::Debug for Prbar {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "PRBAR {{ BASE=0x{:08x} SH=0b{:02b} AP=0b{:02b} XN={} }}",
+            self.base().value() << 6,
+            self.sh().value(),
+            self.ap().value(),
+            self.xn() as u8
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Prbar {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "PRBAR {{ BASE=0x{0=u32:08x} SH={1=3..5} AP={1=1..3} XN={1=0..1} }}",
+            self.0 & !0x3F,
+            self.0
+        )
+    }
+}
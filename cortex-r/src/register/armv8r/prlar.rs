@@ -0,0 +1,83 @@
+//! Code for managing the *Protection Region Limit Address Register*
+
+use arbitrary_int::{u26, u3};
+
+/// The *Protection Region Limit Address Register* (PRLAR)
+///
+/// Controls the EL1 MPU region selected by [`Prselr`](super::Prselr).
+#[bitbybit::bitfield(u32)]
+pub struct Prlar {
+    /// Bits 31:6 of the highest address in the region (the lower six bits are
+    /// taken to be all ones)
+    #[bits(6..=31, rw)]
+    limit: u26,
+    /// Index into the MAIR0/MAIR1 memory attributes
+    #[bits(1..=3, rw)]
+    attrindx: u3,
+    /// Region Enable
+    #[bits(0..=0, rw)]
+    en: bool,
+}
+
+impl Prlar {
+    /// Reads the *Protection Region Limit Address Register*
+    #[inline]
+    pub fn read() -> Prlar {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c6, c3, 1", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+
+    /// Write to the *Protection Region Limit Address Register*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 0, {}, c6, c3, 1", in(reg) _value.raw_value(), options(nomem, nostack, preserves_flags));
+        };
+    }
+
+    /// Modify the *Protection Region Limit Address Register*
+    #[inline]
+    pub fn modify<F>(f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let mut value = Self::read();
+        f(&mut value);
+        Self::write(value);
+    }
+}
+
+impl core::fmt::Debug for Prlar {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "PRLAR {{ LIMIT=0x{:08x} ATTRINDX={} EN={} }}",
+            (self.limit().value() << 6) | 0x3F,
+            self.attrindx(),
+            self.en() as u8
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Prlar {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "PRLAR {{ LIMIT=0x{0=u32:08x} ATTRINDX={1=1..4} EN={1=0..1} }}",
+            self.0 | 0x3F,
+            self.0
+        )
+    }
+}
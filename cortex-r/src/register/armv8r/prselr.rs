@@ -0,0 +1,53 @@
+//! Code for managing the *Protection Region Selection Register*
+
+/// The *Protection Region Selection Register* (PRSELR)
+///
+/// Selects which EL1 MPU region is accessed through [`Prbar`](super::Prbar)
+/// and [`Prlar`](super::Prlar).
+#[bitbybit::bitfield(u32)]
+pub struct Prselr {
+    /// The currently selected MPU region
+    #[bits(0..=7, rw)]
+    region: u8,
+}
+
+impl Prselr {
+    /// Reads the *Protection Region Selection Register*
+    #[inline]
+    pub fn read() -> Prselr {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c6, c2, 1", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+
+    /// Write to the *Protection Region Selection Register*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 0, {}, c6, c2, 1", in(reg) _value.raw_value(), options(nomem, nostack, preserves_flags));
+        };
+    }
+}
+
+impl core::fmt::Debug for Prselr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "PRSELR {{ REGION={} }}", self.region())
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Prselr {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "PRSELR {{ REGION={0=0..8} }}", self.0)
+    }
+}
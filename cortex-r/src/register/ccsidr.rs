@@ -0,0 +1,97 @@
+//! Code for managing the *Cache Size ID Register*
+
+use arbitrary_int::{u10, u15, u3};
+
+/// The *Cache Size ID Register* (CCSIDR)
+///
+/// Describes the cache selected by [`Csselr`](super::Csselr).
+#[bitbybit::bitfield(u32)]
+pub struct Ccsidr {
+    /// Supports Write-Through
+    #[bits(31..=31, r)]
+    wt: bool,
+    /// Supports Write-Back
+    #[bits(30..=30, r)]
+    wb: bool,
+    /// Supports Read-Allocation
+    #[bits(29..=29, r)]
+    ra: bool,
+    /// Supports Write-Allocation
+    #[bits(28..=28, r)]
+    wa: bool,
+    /// The number of sets, minus one
+    #[bits(13..=27, r)]
+    num_sets: u15,
+    /// The associativity (number of ways), minus one
+    #[bits(3..=12, r)]
+    associativity: u10,
+    /// The line size, as log2 of the number of words, minus two
+    #[bits(0..=2, r)]
+    line_size: u3,
+}
+
+impl Ccsidr {
+    /// Reads the *Cache Size ID Register*
+    ///
+    /// You must write to [`Csselr`](super::Csselr) and execute an ISB first,
+    /// to select which cache is described.
+    #[inline]
+    pub fn read() -> Ccsidr {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 1, {}, c0, c0, 0", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+
+    /// The number of sets in the cache
+    #[inline]
+    pub fn sets(&self) -> u32 {
+        u32::from(self.num_sets().value()) + 1
+    }
+
+    /// The number of ways in the cache
+    #[inline]
+    pub fn ways(&self) -> u32 {
+        u32::from(self.associativity().value()) + 1
+    }
+
+    /// The size of a cache line, as log2 of the number of bytes
+    #[inline]
+    pub fn line_size_log2(&self) -> u32 {
+        u32::from(self.line_size().value()) + 4
+    }
+}
+
+impl core::fmt::Debug for Ccsidr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "CCSIDR {{ WT={} WB={} RA={} WA={} NumSets={} Associativity={} LineSize={} }}",
+            self.wt() as u8,
+            self.wb() as u8,
+            self.ra() as u8,
+            self.wa() as u8,
+            self.num_sets(),
+            self.associativity(),
+            self.line_size()
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Ccsidr {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "CCSIDR {{ WT={0=31..32} WB={0=30..31} RA={0=29..30} WA={0=28..29} NumSets={0=13..28} Associativity={0=3..13} LineSize={0=0..3} }}",
+            self.0
+        )
+    }
+}
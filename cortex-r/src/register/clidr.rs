@@ -0,0 +1,127 @@
+//! Code for managing the *Cache Level ID Register*
+
+use arbitrary_int::u3;
+
+/// The *Cache Level ID Register* (CLIDR)
+#[bitbybit::bitfield(u32)]
+pub struct Clidr {
+    /// Level of Unification Uniprocessor
+    #[bits(27..=29, r)]
+    louu: u3,
+    /// Level of Coherence
+    #[bits(24..=26, r)]
+    loc: u3,
+    /// Level of Unification Inner Shareable
+    #[bits(21..=23, r)]
+    louis: u3,
+    /// Cache Type for level 7
+    #[bits(18..=20, r)]
+    ctype7: u3,
+    /// Cache Type for level 6
+    #[bits(15..=17, r)]
+    ctype6: u3,
+    /// Cache Type for level 5
+    #[bits(12..=14, r)]
+    ctype5: u3,
+    /// Cache Type for level 4
+    #[bits(9..=11, r)]
+    ctype4: u3,
+    /// Cache Type for level 3
+    #[bits(6..=8, r)]
+    ctype3: u3,
+    /// Cache Type for level 2
+    #[bits(3..=5, r)]
+    ctype2: u3,
+    /// Cache Type for level 1
+    #[bits(0..=2, r)]
+    ctype1: u3,
+}
+
+/// The type of cache implemented at a particular level, as given in
+/// [`Clidr`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CacheType {
+    /// No cache
+    None,
+    /// Instruction cache only
+    Instruction,
+    /// Data cache only
+    Data,
+    /// Separate instruction and data caches
+    Separate,
+    /// Unified cache
+    Unified,
+    /// A reserved value
+    Reserved(u8),
+}
+
+impl Clidr {
+    /// Reads the *Cache Level ID Register*
+    #[inline]
+    pub fn read() -> Clidr {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 1, {}, c0, c0, 1", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+
+    /// The type of cache at the given level, where level 1 is the cache
+    /// closest to the processor
+    ///
+    /// Returns `None` if the level is not in the range 1 to 7.
+    pub fn cache_type(&self, level: u8) -> Option<CacheType> {
+        let ctype = match level {
+            1 => self.ctype1(),
+            2 => self.ctype2(),
+            3 => self.ctype3(),
+            4 => self.ctype4(),
+            5 => self.ctype5(),
+            6 => self.ctype6(),
+            7 => self.ctype7(),
+            _ => return None,
+        };
+        let ctype = match ctype.value() {
+            0b000 => CacheType::None,
+            0b001 => CacheType::Instruction,
+            0b010 => CacheType::Data,
+            0b011 => CacheType::Separate,
+            0b100 => CacheType::Unified,
+            other => CacheType::Reserved(other),
+        };
+        Some(ctype)
+    }
+}
+
+impl core::fmt::Debug for Clidr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "CLIDR {{ LoUU={} LoC={} LoUIS={} Ctype1={:#05b} Ctype2={:#05b} Ctype3={:#05b} }}",
+            self.louu(),
+            self.loc(),
+            self.louis(),
+            self.ctype1().value(),
+            self.ctype2().value(),
+            self.ctype3().value()
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Clidr {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "CLIDR {{ LoUU={0=27..30} LoC={0=24..27} LoUIS={0=21..24} Ctype1={0=0..3:03b} Ctype2={0=3..6:03b} Ctype3={0=6..9:03b} }}",
+            self.0
+        )
+    }
+}
@@ -0,0 +1,62 @@
+//! Code for managing the *Cache Size Selection Register*
+
+use arbitrary_int::u3;
+
+/// The *Cache Size Selection Register* (CSSELR)
+///
+/// Selects which cache is described by [`Ccsidr`](super::Ccsidr).
+#[bitbybit::bitfield(u32)]
+pub struct Csselr {
+    /// The cache level, minus one
+    #[bits(1..=3, rw)]
+    level: u3,
+    /// Instruction not Data bit
+    #[bits(0..=0, rw)]
+    ind: bool,
+}
+
+impl Csselr {
+    /// Reads the *Cache Size Selection Register*
+    #[inline]
+    pub fn read() -> Csselr {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 2, {}, c0, c0, 0", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+
+    /// Write to the *Cache Size Selection Register*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 2, {}, c0, c0, 0", in(reg) _value.raw_value(), options(nomem, nostack, preserves_flags));
+        };
+    }
+}
+
+impl core::fmt::Debug for Csselr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "CSSELR {{ Level={} InD={} }}",
+            self.level(),
+            self.ind() as u8
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Csselr {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "CSSELR {{ Level={0=1..4} InD={0=0..1} }}", self.0)
+    }
+}
@@ -0,0 +1,82 @@
+//! Code for managing the *Cache Type Register*
+
+use arbitrary_int::{u2, u3, u4};
+
+/// The *Cache Type Register* (CTR)
+#[bitbybit::bitfield(u32)]
+pub struct Ctr {
+    /// Format of this register (0b100 for Armv7 and later)
+    #[bits(29..=31, r)]
+    format: u3,
+    /// Cache Writeback Granule, as log2 of the number of words
+    #[bits(24..=27, r)]
+    cwg: u4,
+    /// Exclusives Reservation Granule, as log2 of the number of words
+    #[bits(20..=23, r)]
+    erg: u4,
+    /// Smallest data cache line size, as log2 of the number of words
+    #[bits(16..=19, r)]
+    dminline: u4,
+    /// Level 1 Instruction cache policy
+    #[bits(14..=15, r)]
+    l1ip: u2,
+    /// Smallest instruction cache line size, as log2 of the number of words
+    #[bits(0..=3, r)]
+    iminline: u4,
+}
+
+impl Ctr {
+    /// Reads the *Cache Type Register*
+    #[inline]
+    pub fn read() -> Ctr {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c0, c0, 1", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+
+    /// The smallest data cache line size, in bytes
+    #[inline]
+    pub fn dcache_line_size(&self) -> usize {
+        4 << self.dminline().value()
+    }
+
+    /// The smallest instruction cache line size, in bytes
+    #[inline]
+    pub fn icache_line_size(&self) -> usize {
+        4 << self.iminline().value()
+    }
+}
+
+impl core::fmt::Debug for Ctr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "CTR {{ Format={:#05b} CWG={} ERG={} DminLine={} L1Ip={:#04b} IminLine={} }}",
+            self.format().value(),
+            self.cwg(),
+            self.erg(),
+            self.dminline(),
+            self.l1ip().value(),
+            self.iminline()
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Ctr {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "CTR {{ Format={0=29..32:03b} CWG={0=24..28} ERG={0=20..24} DminLine={0=16..20} L1Ip={0=14..16:02b} IminLine={0=0..4} }}",
+            self.0
+        )
+    }
+}
@@ -0,0 +1,52 @@
+//! Code for managing the *Data Fault Address Register*
+
+/// The *Data Fault Address Register* (DFAR)
+///
+/// Holds the address of the access which caused the most recent synchronous
+/// Data Abort.
+///
+/// There is no `modify` method because this register holds a single 32-bit address.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Dfar(pub u32);
+
+impl Dfar {
+    /// Reads the *Data Fault Address Register*
+    #[inline]
+    pub fn read() -> Dfar {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c6, c0, 0", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self(r)
+    }
+
+    /// Write to the *Data Fault Address Register*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 0, {}, c6, c0, 0", in(reg) _value.0, options(nomem, nostack, preserves_flags));
+        };
+    }
+}
+
+impl core::fmt::Debug for Dfar {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "DFAR {{ 0x{:08x} }}", self.0)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Dfar {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "DFAR {{ 0x{=u32:08x} }}", self.0)
+    }
+}
@@ -0,0 +1,52 @@
+//! Code for managing the *Instruction Fault Address Register*
+
+/// The *Instruction Fault Address Register* (IFAR)
+///
+/// Holds the address of the instruction fetch which caused the most recent
+/// Prefetch Abort.
+///
+/// There is no `modify` method because this register holds a single 32-bit address.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Ifar(pub u32);
+
+impl Ifar {
+    /// Reads the *Instruction Fault Address Register*
+    #[inline]
+    pub fn read() -> Ifar {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c6, c0, 2", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self(r)
+    }
+
+    /// Write to the *Instruction Fault Address Register*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 0, {}, c6, c0, 2", in(reg) _value.0, options(nomem, nostack, preserves_flags));
+        };
+    }
+}
+
+impl core::fmt::Debug for Ifar {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "IFAR {{ 0x{:08x} }}", self.0)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Ifar {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "IFAR {{ 0x{=u32:08x} }}", self.0)
+    }
+}
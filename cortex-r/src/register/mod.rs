@@ -1,17 +1,67 @@
 //! Access registers in Armv7-R and Armv8-R
 
+mod ccsidr;
+#[doc(inline)]
+pub use ccsidr::Ccsidr;
+
+pub mod clidr;
+#[doc(inline)]
+pub use clidr::Clidr;
+
 pub mod cpsr;
 #[doc(inline)]
 pub use cpsr::Cpsr;
 
+mod csselr;
+#[doc(inline)]
+pub use csselr::Csselr;
+
+mod ctr;
+#[doc(inline)]
+pub use ctr::Ctr;
+
+mod dfar;
+#[doc(inline)]
+pub use dfar::Dfar;
+
+mod ifar;
+#[doc(inline)]
+pub use ifar::Ifar;
+
 mod midr;
 #[doc(inline)]
 pub use midr::Midr;
 
+mod mpidr;
+#[doc(inline)]
+pub use mpidr::Mpidr;
+
+mod mpuir;
+#[doc(inline)]
+pub use mpuir::Mpuir;
+
+mod pmccntr;
+#[doc(inline)]
+pub use pmccntr::Pmccntr;
+
+mod pmcntenset;
+#[doc(inline)]
+pub use pmcntenset::Pmcntenset;
+
+mod pmcr;
+#[doc(inline)]
+pub use pmcr::Pmcr;
+
 mod sctlr;
 #[doc(inline)]
 pub use sctlr::Sctlr;
 
+#[cfg(arm_architecture = "v7-r")]
+mod armv7r;
+#[doc(inline)]
+#[cfg(arm_architecture = "v7-r")]
+pub use armv7r::*;
+
 #[cfg(arm_architecture = "v8-r")]
 mod armv8r;
 #[doc(inline)]
@@ -20,30 +70,12 @@ pub use armv8r::*;
 
 // TODO:
 
-// Multiprocessor Affinity Register (MPIDR)
-
 // System Control Register
 
 // Auxilliary Control Register
 
 // Coprocessor Access Control Register
 
-// Data Fault Status Register
-
-// Instruction Fault Status Register
-
-// Data Fault Address Register
-
-// Instruction Fault Address Register
-
-// MPU Region Base Address Register
-
-// MPU Region Size and Enable Register
-
-// MPU Region Access Control Register
-
-// MPU Region Number Register
-
 // Context ID Register
 
 // Software Thread ID Register
@@ -0,0 +1,66 @@
+//! Code for managing the *Multiprocessor Affinity Register*
+
+/// The *Multiprocessor Affinity Register* (MPIDR)
+///
+/// Identifies this core within a multi-core cluster.
+#[bitbybit::bitfield(u32)]
+pub struct Mpidr {
+    /// Uniprocessor (set if this core is not part of a cluster)
+    #[bits(30..=30, r)]
+    u: bool,
+    /// Multithreading (set if affinity level 0 identifies a thread)
+    #[bits(24..=24, r)]
+    mt: bool,
+    /// Affinity level 2
+    #[bits(16..=23, r)]
+    aff2: u8,
+    /// Affinity level 1
+    #[bits(8..=15, r)]
+    aff1: u8,
+    /// Affinity level 0
+    #[bits(0..=7, r)]
+    aff0: u8,
+}
+
+impl Mpidr {
+    /// Reads the *Multiprocessor Affinity Register*
+    #[inline]
+    pub fn read() -> Mpidr {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c0, c0, 5", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+}
+
+impl core::fmt::Debug for Mpidr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "MPIDR {{ U={} MT={} Aff2={} Aff1={} Aff0={} }}",
+            self.u() as u8,
+            self.mt() as u8,
+            self.aff2(),
+            self.aff1(),
+            self.aff0()
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Mpidr {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "MPIDR {{ U={0=30..31} MT={0=24..25} Aff2={0=16..24} Aff1={0=8..16} Aff0={0=0..8} }}",
+            self.0
+        )
+    }
+}
@@ -0,0 +1,59 @@
+//! Code for managing the *MPU Type Register*
+
+/// The *MPU Type Register* (MPUIR)
+///
+/// On Armv8-R, only the `dregion` field is implemented and it gives the number
+/// of EL1 MPU regions.
+#[bitbybit::bitfield(u32)]
+pub struct Mpuir {
+    /// Number of Instruction Regions (if the MPU is not unified)
+    #[bits(16..=23, r)]
+    iregion: u8,
+    /// Number of Data (or Unified) Regions
+    #[bits(8..=15, r)]
+    dregion: u8,
+    /// Non-unified MPU
+    #[bits(0..=0, r)]
+    nu: bool,
+}
+
+impl Mpuir {
+    /// Reads the *MPU Type Register*
+    #[inline]
+    pub fn read() -> Mpuir {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c0, c0, 4", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+}
+
+impl core::fmt::Debug for Mpuir {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "MPUIR {{ IREGION={} DREGION={} nU={} }}",
+            self.iregion(),
+            self.dregion(),
+            self.nu() as u8
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Mpuir {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "MPUIR {{ IREGION={0=16..24} DREGION={0=8..16} nU={0=0..1} }}",
+            self.0
+        )
+    }
+}
@@ -0,0 +1,51 @@
+//! Code for managing the *Performance Monitors Cycle Count Register*
+
+/// The *Performance Monitors Cycle Count Register* (PMCCNTR)
+///
+/// Counts processor clock cycles, while enabled in PMCR and PMCNTENSET.
+///
+/// There is no `modify` method because this register holds a single 32-bit count.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Pmccntr(pub u32);
+
+impl Pmccntr {
+    /// Reads the *Performance Monitors Cycle Count Register*
+    #[inline]
+    pub fn read() -> Pmccntr {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c9, c13, 0", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self(r)
+    }
+
+    /// Write to the *Performance Monitors Cycle Count Register*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 0, {}, c9, c13, 0", in(reg) _value.0, options(nomem, nostack, preserves_flags));
+        };
+    }
+}
+
+impl core::fmt::Debug for Pmccntr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "PMCCNTR {{ {} }}", self.0)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Pmccntr {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "PMCCNTR {{ {=u32} }}", self.0)
+    }
+}
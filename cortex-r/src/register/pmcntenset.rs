@@ -0,0 +1,62 @@
+//! Code for managing the *Performance Monitors Count Enable Set Register*
+
+use arbitrary_int::u31;
+
+/// The *Performance Monitors Count Enable Set Register* (PMCNTENSET)
+///
+/// Writing a one to a bit enables that counter. Writing a zero has no effect.
+#[bitbybit::bitfield(u32)]
+pub struct Pmcntenset {
+    /// The bitmask for the cycle counter enable bit
+    #[bits(31..=31, rw)]
+    c: bool,
+    /// The bitmask for the event counter enable bits, one for each counter
+    #[bits(0..=30, rw)]
+    p: u31,
+}
+
+impl Pmcntenset {
+    /// Reads the *Performance Monitors Count Enable Set Register*
+    #[inline]
+    pub fn read() -> Self {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c9, c12, 1", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+
+    /// Write to the *Performance Monitors Count Enable Set Register*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 0, {}, c9, c12, 1", in(reg) _value.raw_value(), options(nomem, nostack, preserves_flags));
+        };
+    }
+}
+
+impl core::fmt::Debug for Pmcntenset {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "PMCNTENSET {{ C={} P=0x{:08x} }}",
+            self.c() as u8,
+            self.p().value()
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Pmcntenset {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "PMCNTENSET {{ C={0=31..32} P=0x{0=0..31:08x} }}", self.0)
+    }
+}
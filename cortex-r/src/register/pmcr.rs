@@ -0,0 +1,97 @@
+//! Code for managing the *Performance Monitors Control Register*
+
+use arbitrary_int::u5;
+
+/// The *Performance Monitors Control Register* (PMCR)
+#[bitbybit::bitfield(u32)]
+pub struct Pmcr {
+    /// The number of event counters implemented
+    #[bits(11..=15, r)]
+    n: u5,
+    /// The bitmask for the Disable cycle counter in Prohibited regions bit
+    #[bits(5..=5, rw)]
+    dp: bool,
+    /// The bitmask for the Export enable bit
+    #[bits(4..=4, rw)]
+    x: bool,
+    /// The bitmask for the cycle counter Divider bit
+    ///
+    /// When set, the cycle counter counts once every 64 cycles.
+    #[bits(3..=3, rw)]
+    d: bool,
+    /// The bitmask for the Cycle counter reset bit
+    ///
+    /// Writing a one resets the cycle counter to zero. Always reads as zero.
+    #[bits(2..=2, rw)]
+    c: bool,
+    /// The bitmask for the event counter reset bit
+    ///
+    /// Writing a one resets every event counter to zero. Always reads as zero.
+    #[bits(1..=1, rw)]
+    p: bool,
+    /// The bitmask for the Enable bit, which enables every counter
+    #[bits(0..=0, rw)]
+    e: bool,
+}
+
+impl Pmcr {
+    /// Reads the *Performance Monitors Control Register*
+    #[inline]
+    pub fn read() -> Self {
+        let r: u32;
+        // Safety: Reading this register has no side-effects and is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mrc p15, 0, {}, c9, c12, 0", out(reg) r, options(nomem, nostack, preserves_flags));
+        }
+        #[cfg(not(target_arch = "arm"))]
+        {
+            r = 0;
+        }
+        Self::new_with_raw_value(r)
+    }
+
+    /// Write to the *Performance Monitors Control Register*
+    #[inline]
+    pub fn write(_value: Self) {
+        // Safety: Writing this register is atomic
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            core::arch::asm!("mcr p15, 0, {}, c9, c12, 0", in(reg) _value.raw_value(), options(nomem, nostack, preserves_flags));
+        };
+    }
+
+    /// Modify the *Performance Monitors Control Register*
+    #[inline]
+    pub fn modify<F>(f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let mut value = Self::read();
+        f(&mut value);
+        Self::write(value);
+    }
+}
+
+impl core::fmt::Debug for Pmcr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "PMCR {{ N={} DP={} X={} D={} C={} P={} E={} }}",
+            self.n(),
+            self.dp() as u8,
+            self.x() as u8,
+            self.d() as u8,
+            self.c() as u8,
+            self.p() as u8,
+            self.e() as u8,
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Pmcr {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "PMCR {{ N={0=11..16} DP={0=5..6} X={0=4..5} D={0=3..4} C={0=2..3} P={0=1..2} E={0=0..1} }}", self.0)
+    }
+}
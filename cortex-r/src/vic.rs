@@ -0,0 +1,361 @@
+//! A driver for an Arm PrimeCell Vectored Interrupt Controller (PL190 or PL192)
+//!
+//! The VIC combines up to 32 interrupt sources into the IRQ and FIQ lines of a
+//! core. For each source you can supply the address of its handler, and the VIC
+//! gives you the address of the highest priority one when you acknowledge the
+//! interrupt.
+//!
+//! On a core with a VIC port (e.g. the Cortex-R5), setting SCTLR.VE makes the
+//! core fetch that address itself and branch straight to it, skipping the IRQ
+//! entry in the vector table. See the `vectored-interrupts` feature of
+//! `cortex-r-rt`.
+
+use core::sync::atomic::{compiler_fence, Ordering};
+
+use crate::interrupt::InterruptController;
+
+// Registers common to the PL190 and PL192
+const VICIRQSTATUS: usize = 0x000;
+const VICFIQSTATUS: usize = 0x004;
+const VICRAWINTR: usize = 0x008;
+const VICINTSELECT: usize = 0x00C;
+const VICINTENABLE: usize = 0x010;
+const VICINTENCLEAR: usize = 0x014;
+const VICSOFTINT: usize = 0x018;
+const VICSOFTINTCLEAR: usize = 0x01C;
+const VICVECTADDR: usize = 0x100;
+
+// Registers only on the PL190
+const PL190_VICVECTADDR: usize = 0x030;
+const PL190_VICDEFVECTADDR: usize = 0x034;
+const PL190_VICVECTCNTL: usize = 0x200;
+const PL190_VICVECTCNTL_ENABLE: u32 = 1 << 5;
+
+// Registers only on the PL192
+const PL192_VICVECTPRIORITY: usize = 0x200;
+const PL192_VICADDRESS: usize = 0xF00;
+
+/// Which VIC we are driving
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Model {
+    /// The PL190, with 16 vectored slots plus a default vector
+    Pl190,
+    /// The PL192, with a vector and a priority for each of the 32 sources
+    Pl192,
+}
+
+/// A VIC interrupt source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(transparent)]
+pub struct Source(u8);
+
+impl Source {
+    /// The number of interrupt sources a VIC has
+    pub const COUNT: u8 = 32;
+
+    /// Create a source from its number
+    ///
+    /// Panics if `source` is not less than 32.
+    pub const fn new(source: u8) -> Source {
+        assert!(source < Self::COUNT, "invalid VIC source");
+        Source(source)
+    }
+
+    /// Get the source number
+    pub const fn to_raw(self) -> u8 {
+        self.0
+    }
+
+    /// The bit for this source in the status and enable registers
+    const fn mask(self) -> u32 {
+        1 << self.0
+    }
+}
+
+impl From<Source> for u32 {
+    fn from(source: Source) -> u32 {
+        u32::from(source.to_raw())
+    }
+}
+
+/// A driver for a PL190 or PL192 VIC
+pub struct Vic {
+    base: *mut u32,
+    model: Model,
+}
+
+// Safety: the driver only holds the address of the VIC. Anything which
+// changes the configuration of the VIC takes `&mut self`, and the rest is
+// single register accesses.
+unsafe impl Send for Vic {}
+unsafe impl Sync for Vic {}
+
+impl Vic {
+    /// Create a driver for the VIC at the given address
+    ///
+    /// # Safety
+    ///
+    /// The address must point at a VIC of the given model. There must only be
+    /// one driver for it at any one time.
+    pub unsafe fn new(base: *mut u32, model: Model) -> Vic {
+        Vic { base, model }
+    }
+
+    /// Which model of VIC this is
+    pub fn model(&self) -> Model {
+        self.model
+    }
+
+    /// Initialise the VIC
+    ///
+    /// Disables every source, clears every software interrupt, makes every
+    /// source an IRQ, and clears every vector (on the PL192, also setting
+    /// every source to the lowest priority).
+    pub fn init(&mut self) {
+        // Safety: the address was given to us in `new`
+        unsafe {
+            self.write(VICINTENCLEAR, 0xFFFF_FFFF);
+            self.write(VICSOFTINTCLEAR, 0xFFFF_FFFF);
+            self.write(VICINTSELECT, 0);
+            match self.model {
+                Model::Pl190 => {
+                    for slot in 0..16 {
+                        self.write(VICVECTADDR + slot * 4, 0);
+                        self.write(PL190_VICVECTCNTL + slot * 4, 0);
+                    }
+                    self.write(PL190_VICDEFVECTADDR, 0);
+                }
+                Model::Pl192 => {
+                    for source in 0..usize::from(Source::COUNT) {
+                        self.write(VICVECTADDR + source * 4, 0);
+                        self.write(PL192_VICVECTPRIORITY + source * 4, 0xF);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Enable or disable an interrupt source
+    pub fn enable_interrupt(&mut self, source: Source, enable: bool) {
+        let offset = if enable { VICINTENABLE } else { VICINTENCLEAR };
+        // Safety: the address was given to us in `new`
+        unsafe {
+            self.write(offset, source.mask());
+        }
+    }
+
+    /// Raise or clear a software interrupt on a source
+    pub fn set_pending(&mut self, source: Source, pending: bool) {
+        let offset = if pending { VICSOFTINT } else { VICSOFTINTCLEAR };
+        // Safety: the address was given to us in `new`
+        unsafe {
+            self.write(offset, source.mask());
+        }
+    }
+
+    /// Is the source asserting its interrupt (whether or not it is enabled)?
+    pub fn is_raw_pending(&self, source: Source) -> bool {
+        // Safety: the address was given to us in `new`
+        unsafe { self.read(VICRAWINTR) & source.mask() != 0 }
+    }
+
+    /// Is the source enabled and signalling an IRQ?
+    pub fn is_irq_pending(&self, source: Source) -> bool {
+        // Safety: the address was given to us in `new`
+        unsafe { self.read(VICIRQSTATUS) & source.mask() != 0 }
+    }
+
+    /// Is the source enabled and signalling an FIQ?
+    pub fn is_fiq_pending(&self, source: Source) -> bool {
+        // Safety: the address was given to us in `new`
+        unsafe { self.read(VICFIQSTATUS) & source.mask() != 0 }
+    }
+
+    /// Set whether a source is signalled as FIQ (rather than IRQ)
+    ///
+    /// FIQ sources are not vectored.
+    pub fn set_fiq(&mut self, source: Source, fiq: bool) {
+        // Safety: the address was given to us in `new`
+        unsafe {
+            let select = self.read(VICINTSELECT);
+            let select = if fiq {
+                select | source.mask()
+            } else {
+                select & !source.mask()
+            };
+            self.write(VICINTSELECT, select);
+        }
+    }
+
+    /// Set the handler and priority of a source
+    ///
+    /// With SCTLR.VE set, the core branches straight to `handler`, so it is
+    /// usually a trampoline made with `cortex_r_rt::vectored_interrupt!`.
+    /// Lower values are higher priority. On the PL192, `priority` must be less
+    /// than 16, and each source has its own priority. On the PL190, `priority`
+    /// is the vectored slot (0 to 15) to use, replacing whichever source was in
+    /// that slot before.
+    ///
+    /// Panics if `priority` is not less than 16.
+    pub fn set_vector(&mut self, source: Source, handler: unsafe extern "C" fn(), priority: u8) {
+        assert!(priority < 16, "invalid VIC priority");
+        let priority = usize::from(priority);
+        // Safety: the address was given to us in `new`
+        unsafe {
+            match self.model {
+                Model::Pl190 => {
+                    self.write(VICVECTADDR + priority * 4, handler as usize as u32);
+                    self.write(
+                        PL190_VICVECTCNTL + priority * 4,
+                        PL190_VICVECTCNTL_ENABLE | u32::from(source.to_raw()),
+                    );
+                }
+                Model::Pl192 => {
+                    let source = usize::from(source.to_raw());
+                    self.write(VICVECTADDR + source * 4, handler as usize as u32);
+                    self.write(PL192_VICVECTPRIORITY + source * 4, priority as u32);
+                }
+            }
+        }
+    }
+
+    /// Set the handler for sources without a vectored slot
+    ///
+    /// Only the PL190 has this; on the PL192 it does nothing.
+    pub fn set_default_vector(&mut self, handler: unsafe extern "C" fn()) {
+        if self.model == Model::Pl190 {
+            // Safety: the address was given to us in `new`
+            unsafe {
+                self.write(PL190_VICDEFVECTADDR, handler as usize as u32);
+            }
+        }
+    }
+
+    /// Acknowledge the highest priority pending IRQ, and get its handler
+    /// address
+    ///
+    /// Lower priority interrupts are held off until you call
+    /// [`Vic::end_interrupt`]. With SCTLR.VE set, the core does this for you
+    /// before branching to the handler.
+    pub fn acknowledge_vector(&self) -> usize {
+        // Safety: the address was given to us in `new`
+        let handler = unsafe { self.read(self.address_register()) };
+        compiler_fence(Ordering::SeqCst);
+        handler as usize
+    }
+
+    /// Acknowledge the highest priority pending IRQ, and get its source
+    ///
+    /// This is for when you dispatch interrupts by source rather than by
+    /// handler address. Returns `None` if no IRQ is pending.
+    pub fn acknowledge(&self) -> Option<Source> {
+        self.acknowledge_vector();
+        // Safety: the address was given to us in `new`
+        let status = unsafe { self.read(VICIRQSTATUS) };
+        if status == 0 {
+            // Spurious, or already dealt with. Reading the address register
+            // still updated the priority hardware, and nobody will call
+            // `end_interrupt` for this, so write it back here.
+            compiler_fence(Ordering::SeqCst);
+            // Safety: the address was given to us in `new`
+            unsafe {
+                self.write(self.address_register(), 0);
+            }
+            return None;
+        }
+        match self.model {
+            // The first vectored slot with a pending source wins, and otherwise
+            // it's whichever non-vectored source
+            Model::Pl190 => (0..16)
+                .find_map(|slot| {
+                    // Safety: the address was given to us in `new`
+                    let cntl = unsafe { self.read(PL190_VICVECTCNTL + slot * 4) };
+                    let source = Source((cntl & 0x1F) as u8);
+                    let vectored = cntl & PL190_VICVECTCNTL_ENABLE != 0;
+                    (vectored && status & source.mask() != 0).then_some(source)
+                })
+                .or(Some(Source(status.trailing_zeros() as u8))),
+            // The pending source with the highest priority wins
+            Model::Pl192 => (0..Source::COUNT)
+                .map(Source)
+                .filter(|source| status & source.mask() != 0)
+                .min_by_key(|source| {
+                    let offset = PL192_VICVECTPRIORITY + usize::from(source.0) * 4;
+                    // Safety: the address was given to us in `new`
+                    unsafe { self.read(offset) & 0xF }
+                }),
+        }
+    }
+
+    /// Tell the VIC the current interrupt has been handled
+    ///
+    /// Call this at the end of every IRQ handler, even when SCTLR.VE is set.
+    /// The VIC only tracks priorities, so `source` is not checked, but it should
+    /// be the one you acknowledged.
+    pub fn end_interrupt(&self, _source: Source) {
+        compiler_fence(Ordering::SeqCst);
+        // Safety: the address was given to us in `new`
+        unsafe {
+            self.write(self.address_register(), 0);
+        }
+    }
+
+    /// The offset of the register holding the current handler address
+    fn address_register(&self) -> usize {
+        match self.model {
+            Model::Pl190 => PL190_VICVECTADDR,
+            Model::Pl192 => PL192_VICADDRESS,
+        }
+    }
+
+    /// Read the register at the given byte offset
+    unsafe fn read(&self, offset: usize) -> u32 {
+        // Safety: the caller has promised this register exists
+        unsafe { self.base.byte_add(offset).read_volatile() }
+    }
+
+    /// Write the register at the given byte offset
+    unsafe fn write(&self, offset: usize, value: u32) {
+        // Safety: the caller has promised this register exists
+        unsafe { self.base.byte_add(offset).write_volatile(value) }
+    }
+}
+
+/// On the PL192, the top four bits of the priority are used. The PL190 has no
+/// priority for each source (see [`Vic::set_vector`]), so `set_priority` does
+/// nothing on it.
+impl InterruptController for Vic {
+    type Id = Source;
+
+    fn enable(&mut self, id: Source) {
+        self.enable_interrupt(id, true);
+    }
+
+    fn disable(&mut self, id: Source) {
+        self.enable_interrupt(id, false);
+    }
+
+    fn set_pending(&mut self, id: Source, pending: bool) {
+        Vic::set_pending(self, id, pending);
+    }
+
+    fn set_priority(&mut self, id: Source, priority: u8) {
+        if self.model == Model::Pl192 {
+            let offset = PL192_VICVECTPRIORITY + usize::from(id.0) * 4;
+            // Safety: the address was given to us in `new`
+            unsafe {
+                self.write(offset, u32::from(priority >> 4));
+            }
+        }
+    }
+
+    fn acknowledge(&self) -> Option<Source> {
+        Vic::acknowledge(self)
+    }
+
+    fn end_interrupt(&self, id: Source) {
+        Vic::end_interrupt(self, id);
+    }
+}